@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::BufRead;
 
 use anyhow::{Context, Result};
@@ -35,6 +35,101 @@ pub struct Header {
     pub theme: Option<Theme>,
 }
 
+/// Header layout of asciicast v3 files, normalized into [`Header`].
+#[derive(Debug, Deserialize)]
+struct HeaderV3 {
+    version: u32,
+    term: TermV3,
+    timestamp: Option<f64>,
+    idle_time_limit: Option<f64>,
+    command: Option<String>,
+    title: Option<String>,
+    env: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TermV3 {
+    cols: u32,
+    rows: u32,
+    #[serde(rename = "type")]
+    term_type: Option<String>,
+    theme: Option<Theme>,
+}
+
+impl From<HeaderV3> for Header {
+    fn from(v3: HeaderV3) -> Self {
+        // v2 records the terminal type as TERM in env; keep it discoverable there
+        let env = match (v3.env, v3.term.term_type) {
+            (Some(mut env), Some(term_type)) => {
+                env.entry("TERM".to_string()).or_insert(term_type);
+                Some(env)
+            }
+            (None, Some(term_type)) => Some(HashMap::from([("TERM".to_string(), term_type)])),
+            (env, None) => env,
+        };
+
+        Header {
+            version: v3.version,
+            width: v3.term.cols,
+            height: v3.term.rows,
+            timestamp: v3.timestamp,
+            duration: None,
+            idle_time_limit: v3.idle_time_limit,
+            command: v3.command,
+            title: v3.title,
+            env,
+            theme: v3.term.theme,
+        }
+    }
+}
+
+/// An asciicast v1 recording: a single JSON document with `[delay, data]`
+/// output pairs, normalized into [`Header`] and absolute-time [`Event`]s.
+#[derive(Debug, Deserialize)]
+struct CastV1 {
+    version: u32,
+    width: u32,
+    height: u32,
+    duration: Option<f64>,
+    command: Option<String>,
+    title: Option<String>,
+    env: Option<HashMap<String, String>>,
+    stdout: Vec<(f64, String)>,
+}
+
+impl CastV1 {
+    fn into_parts(self) -> (Header, VecDeque<Event>) {
+        let mut time = 0.0;
+        let events = self
+            .stdout
+            .into_iter()
+            .map(|(delay, data)| {
+                time += delay;
+                Event {
+                    time,
+                    event_type: EventType::Output,
+                    data,
+                }
+            })
+            .collect();
+
+        let header = Header {
+            version: self.version,
+            width: self.width,
+            height: self.height,
+            timestamp: None,
+            duration: self.duration,
+            idle_time_limit: None,
+            command: self.command,
+            title: self.title,
+            env: self.env,
+            theme: None,
+        };
+
+        (header, events)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Event {
     pub time: f64,
@@ -47,6 +142,8 @@ pub enum EventType {
     Output,
     Input,
     Resize,
+    Marker,
+    Exit,
 }
 
 impl Event {
@@ -63,6 +160,8 @@ impl Event {
             "o" => EventType::Output,
             "i" => EventType::Input,
             "r" => EventType::Resize,
+            "m" => EventType::Marker,
+            "x" => EventType::Exit,
             _ => {
                 log::warn!("Unknown event type: {}, treating as output", event_type_str);
                 EventType::Output
@@ -82,9 +181,24 @@ impl Event {
     }
 }
 
+impl Event {
+    /// Returns the `(cols, rows)` carried by a resize event, e.g. `"100x30"`.
+    pub fn resize_dimensions(&self) -> Option<(usize, usize)> {
+        if self.event_type != EventType::Resize {
+            return None;
+        }
+        let (cols, rows) = self.data.trim().split_once('x')?;
+        Some((cols.parse().ok()?, rows.parse().ok()?))
+    }
+}
+
 pub struct Parser<R: BufRead> {
     reader: R,
     header: Option<Header>,
+    /// Accumulated time for formats that store relative event intervals (v3)
+    elapsed: f64,
+    /// Events already decoded along with the header (v1 single-document files)
+    pending: VecDeque<Event>,
 }
 
 impl<R: BufRead> Parser<R> {
@@ -92,6 +206,8 @@ impl<R: BufRead> Parser<R> {
         Self {
             reader,
             header: None,
+            elapsed: 0.0,
+            pending: VecDeque::new(),
         }
     }
 
@@ -105,47 +221,85 @@ impl<R: BufRead> Parser<R> {
             anyhow::bail!("Empty cast file");
         }
 
-        let header: Header = serde_json::from_str(&line).context("Failed to parse header JSON")?;
-
-        if header.version != 2 {
-            anyhow::bail!(
-                "Unsupported asciicast version: {}. Only version 2 is supported.",
-                header.version
-            );
-        }
+        let value: Value = match serde_json::from_str(&line) {
+            Ok(value) => value,
+            Err(e) if e.is_eof() => {
+                // A multi-line JSON document (v1): the header spans the whole input
+                self.reader
+                    .read_to_string(&mut line)
+                    .context("Failed to read cast file")?;
+                serde_json::from_str(&line).context("Failed to parse header JSON")?
+            }
+            Err(e) => return Err(e).context("Failed to parse header JSON"),
+        };
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+        let header: Header = match version {
+            1 => {
+                let cast: CastV1 =
+                    serde_json::from_value(value).context("Failed to parse v1 cast JSON")?;
+                let (header, events) = cast.into_parts();
+                self.pending = events;
+                header
+            }
+            2 => serde_json::from_value(value).context("Failed to parse header JSON")?,
+            3 => serde_json::from_value::<HeaderV3>(value)
+                .context("Failed to parse header JSON")?
+                .into(),
+            _ => anyhow::bail!(
+                "Unsupported asciicast version: {}. Only versions 1, 2 and 3 are supported.",
+                version
+            ),
+        };
 
         self.header = Some(header.clone());
         Ok(header)
     }
 
     pub fn events(&mut self) -> EventIterator<'_, R> {
-        EventIterator {
-            reader: &mut self.reader,
-        }
+        EventIterator { parser: self }
+    }
+
+    fn version(&self) -> u32 {
+        self.header.as_ref().map(|h| h.version).unwrap_or(2)
     }
 }
 
 pub struct EventIterator<'a, R: BufRead> {
-    reader: &'a mut R,
+    parser: &'a mut Parser<R>,
 }
 
 impl<'a, R: BufRead> Iterator for EventIterator<'a, R> {
     type Item = Result<Event>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.parser.pending.pop_front() {
+            return Some(Ok(event));
+        }
+
         let mut line = String::new();
-        match self.reader.read_line(&mut line) {
+        match self.parser.reader.read_line(&mut line) {
             Ok(0) => None, // EOF
             Ok(_) => {
                 let line = line.trim();
-                if line.is_empty() {
-                    return self.next(); // Skip empty lines
+                if line.is_empty() || line.starts_with('#') {
+                    return self.next(); // Skip empty lines and v3 comments
                 }
 
-                match serde_json::from_str::<Vec<Value>>(line) {
-                    Ok(arr) => Some(Event::from_json_array(&arr)),
-                    Err(e) => Some(Err(anyhow::anyhow!("Failed to parse event JSON: {}", e))),
+                let mut event = match serde_json::from_str::<Vec<Value>>(line) {
+                    Ok(arr) => Event::from_json_array(&arr),
+                    Err(e) => Err(anyhow::anyhow!("Failed to parse event JSON: {}", e)),
+                };
+
+                // v3 stores the interval since the previous event; convert to absolute time
+                if self.parser.version() == 3
+                    && let Ok(event) = &mut event
+                {
+                    self.parser.elapsed += event.time;
+                    event.time = self.parser.elapsed;
                 }
+
+                Some(event)
             }
             Err(e) => Some(Err(anyhow::anyhow!("Failed to read line: {}", e))),
         }
@@ -182,3 +336,100 @@ impl Asciicast {
         Ok(Self { header, events })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_with_spec_theme() {
+        let cast = concat!(
+            r##"{"version": 2, "width": 80, "height": 24, "theme": {"fg": "#d0d0d0", "bg": "#212121", "##,
+            r##""palette": "#000000:#aa0000:#00aa00:#aa5500:#0000aa:#aa00aa:#00aaaa:#aaaaaa"}}"##,
+            "\n",
+            r#"[0.5, "o", "hi"]"#,
+            "\n",
+        );
+
+        let (header, events) = parse(cast.as_bytes()).unwrap();
+        let theme = header.theme.unwrap();
+        assert_eq!(theme.fg.to_u32(), 0xd0d0d000);
+        assert_eq!(theme.bg.to_u32(), 0x21212100);
+        assert_eq!(theme.palette[1].to_u32(), 0xaa000000);
+        // 8-color palettes reuse the normal colors for the bright variants
+        assert_eq!(theme.palette[9].to_u32(), 0xaa000000);
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_header_with_malformed_theme() {
+        for theme in [
+            r##"{"fg": "#€€", "bg": "#212121", "palette": "#000000"}"##,
+            r##"{"fg": "#d0d0d0", "bg": "#zzzzzz", "palette": "#000000"}"##,
+            r##"{"fg": "#d0d0d0", "bg": "#212121", "palette": "#000000:#€€"}"##,
+        ] {
+            let cast = format!(r#"{{"version": 2, "width": 80, "height": 24, "theme": {theme}}}"#);
+            let err = parse(cast.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains("Failed to parse header JSON"));
+        }
+    }
+
+    #[test]
+    fn test_parse_v3() {
+        let cast = concat!(
+            r#"{"version": 3, "term": {"cols": 100, "rows": 30, "type": "xterm-256color"}, "title": "demo"}"#,
+            "\n",
+            "# a comment line\n",
+            r#"[0.5, "o", "a"]"#,
+            "\n",
+            r#"[0.25, "m", "chapter"]"#,
+            "\n",
+            r#"[1.0, "r", "90x20"]"#,
+            "\n",
+            r#"[0.25, "x", "0"]"#,
+            "\n",
+        );
+
+        let (header, events) = parse(cast.as_bytes()).unwrap();
+        assert_eq!(header.version, 3);
+        assert_eq!((header.width, header.height), (100, 30));
+        assert_eq!(header.title.as_deref(), Some("demo"));
+        assert_eq!(
+            header.env.unwrap().get("TERM").map(String::as_str),
+            Some("xterm-256color")
+        );
+
+        let times: Vec<f64> = events.iter().map(|e| e.time).collect();
+        assert_eq!(times, vec![0.5, 0.75, 1.75, 2.0]);
+        assert_eq!(events[1].event_type, EventType::Marker);
+        assert_eq!(events[2].event_type, EventType::Resize);
+        assert_eq!(events[3].event_type, EventType::Exit);
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let cast = r#"{
+  "version": 1,
+  "width": 80,
+  "height": 24,
+  "duration": 1.5,
+  "command": "/bin/bash",
+  "env": {"TERM": "xterm-256color", "SHELL": "/bin/bash"},
+  "stdout": [
+    [0.5, "$ "],
+    [0.25, "ls\r\n"],
+    [0.75, "file.txt\r\n"]
+  ]
+}"#;
+
+        let (header, events) = parse(cast.as_bytes()).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!((header.width, header.height), (80, 24));
+        assert_eq!(header.command.as_deref(), Some("/bin/bash"));
+
+        let times: Vec<f64> = events.iter().map(|e| e.time).collect();
+        assert_eq!(times, vec![0.5, 0.75, 1.5]);
+        assert!(events.iter().all(|e| e.event_type == EventType::Output));
+        assert_eq!(events[1].data, "ls\r\n");
+    }
+}
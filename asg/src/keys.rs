@@ -0,0 +1,180 @@
+/// A key label shown by the keystroke overlay, at a time on the output timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keystroke {
+    pub time: f64,
+    pub label: String,
+}
+
+/// A piece of overlay text, visible from `start` until `end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlaySegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// Whether this is the last segment of a burst of typing and should fade out
+    pub fade_out: bool,
+}
+
+/// Maximum number of labels kept on screen at once
+const MAX_LABELS: usize = 24;
+
+/// Splits raw input data (as recorded in "i" events) into readable key labels,
+/// e.g. `"ls\r"` becomes `["l", "s", "⏎"]` and `"\x1b[A"` becomes `["↑"]`.
+pub fn describe_input(data: &str) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut rest = data;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let (label, len) = describe_escape(rest);
+            labels.push(label);
+            rest = &rest[len..];
+            continue;
+        }
+
+        let label = match c {
+            '\r' | '\n' => "⏎".to_string(),
+            '\t' => "⇥".to_string(),
+            ' ' => "␣".to_string(),
+            '\x7f' | '\x08' => "⌫".to_string(),
+            '\0' => "Ctrl+Space".to_string(),
+            '\x01'..='\x1a' => format!("Ctrl+{}", (b'A' + c as u8 - 1) as char),
+            c if c.is_control() => format!("U+{:04X}", c as u32),
+            c => c.to_string(),
+        };
+        labels.push(label);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    labels
+}
+
+/// Describes the escape sequence at the start of `s`, returning its label and byte length.
+fn describe_escape(s: &str) -> (String, usize) {
+    let bytes = s.as_bytes();
+
+    // CSI (ESC [) and SS3 (ESC O) sequences produced by cursor and function keys
+    if bytes.len() >= 3 && (bytes[1] == b'[' || bytes[1] == b'O') {
+        let end = bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map(|i| i + 3);
+        if let Some(end) = end {
+            let label = match &s[2..end] {
+                "A" => "↑",
+                "B" => "↓",
+                "C" => "→",
+                "D" => "←",
+                "H" | "1~" | "7~" => "Home",
+                "F" | "4~" | "8~" => "End",
+                "2~" => "Ins",
+                "3~" => "Del",
+                "5~" => "PgUp",
+                "6~" => "PgDn",
+                "Z" => "⇧⇥",
+                "P" | "11~" => "F1",
+                "Q" | "12~" => "F2",
+                "R" | "13~" => "F3",
+                "S" | "14~" => "F4",
+                _ => "Esc",
+            };
+            return (label.to_string(), end);
+        }
+    }
+
+    // ESC followed by a printable character is Alt+<key>
+    if let Some(c) = s[1..].chars().next()
+        && !c.is_control()
+        && c != '['
+    {
+        return (format!("Alt+{}", c), 1 + c.len_utf8());
+    }
+
+    ("Esc".to_string(), 1)
+}
+
+/// Groups keystrokes into overlay segments. Keys typed within `timeout`
+/// seconds of each other accumulate on one line; after a pause the line
+/// fades out and the next key starts a new one.
+pub fn overlay_segments(keystrokes: &[Keystroke], timeout: f64) -> Vec<OverlaySegment> {
+    let mut segments = Vec::new();
+    let mut labels: Vec<&str> = Vec::new();
+
+    for (i, key) in keystrokes.iter().enumerate() {
+        let continues = i > 0 && key.time - keystrokes[i - 1].time <= timeout;
+        if !continues {
+            labels.clear();
+        }
+        labels.push(&key.label);
+        if labels.len() > MAX_LABELS {
+            labels.remove(0);
+        }
+
+        let next = keystrokes
+            .get(i + 1)
+            .filter(|next| next.time - key.time <= timeout);
+        let (end, fade_out) = match next {
+            Some(next) => (next.time, false),
+            None => (key.time + timeout, true),
+        };
+
+        segments.push(OverlaySegment {
+            start: key.time,
+            end,
+            text: join_labels(&labels),
+            fade_out,
+        });
+    }
+
+    segments
+}
+
+/// Joins labels for display: single characters are concatenated, named keys
+/// are separated by spaces.
+fn join_labels(labels: &[&str]) -> String {
+    let mut text = String::new();
+    let mut prev_named = false;
+
+    for label in labels {
+        let named = label.chars().count() > 1;
+        if !text.is_empty() && (named || prev_named) {
+            text.push(' ');
+        }
+        text.push_str(label);
+        prev_named = named;
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_input() {
+        assert_eq!(describe_input("ls\r"), vec!["l", "s", "⏎"]);
+        assert_eq!(describe_input("\x1b[A\x1bOB"), vec!["↑", "↓"]);
+        assert_eq!(describe_input("\x03\x7f"), vec!["Ctrl+C", "⌫"]);
+        assert_eq!(describe_input("\x1bb\x1b"), vec!["Alt+b", "Esc"]);
+    }
+
+    #[test]
+    fn test_overlay_segments() {
+        let keys: Vec<Keystroke> = [(0.0, "l"), (0.2, "s"), (0.4, "⏎"), (5.0, "Ctrl+C")]
+            .into_iter()
+            .map(|(time, label)| Keystroke {
+                time,
+                label: label.to_string(),
+            })
+            .collect();
+
+        let segments = overlay_segments(&keys, 1.0);
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["l", "ls", "ls⏎", "Ctrl+C"]);
+        assert_eq!(segments[1].end, 0.4);
+        assert!(!segments[1].fade_out);
+        assert!((segments[2].end - 1.4).abs() < 1e-9);
+        assert!(segments[2].fade_out);
+    }
+}
@@ -1,5 +1,6 @@
 pub mod asciicast;
 pub mod input;
+pub mod keys;
 pub mod renderer;
 pub mod terminal;
 pub mod theme;
@@ -83,9 +84,14 @@ pub struct Config {
     pub idle_time_limit: Option<f64>,
     pub loop_enable: bool,
     pub at: Option<f64>,
+    pub transcript: bool,
+    pub cut_alt_screen: bool,
     pub from: Option<f64>,
     pub to: Option<f64>,
     pub no_cursor: bool,
+    pub cursor_blink: bool,
+    pub keys: bool,
+    pub keys_duration: f64,
     pub window: bool,
     pub padding: u16,
     pub padding_x: Option<u16>,
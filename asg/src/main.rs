@@ -3,6 +3,7 @@ use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::Result;
+use asg::asciicast::Event;
 use asg::{self, Config, Theme};
 use clap::Parser;
 use clap::ValueEnum;
@@ -102,6 +103,14 @@ struct Cli {
     #[clap(long)]
     at: Option<f64>,
 
+    /// Render the whole session (scrollback and final screen) as one static image
+    #[clap(long, conflicts_with = "at")]
+    transcript: bool,
+
+    /// Leave full-screen programs (alternate screen) out of the transcript
+    #[clap(long, requires = "transcript")]
+    cut_alt_screen: bool,
+
     /// Lower range of timeline to render in seconds
     #[clap(long)]
     from: Option<f64>,
@@ -114,6 +123,18 @@ struct Cli {
     #[clap(long)]
     no_cursor: bool,
 
+    /// Animate blinking cursor styles
+    #[clap(long)]
+    cursor_blink: bool,
+
+    /// Show typed keys (from input events) as an overlay
+    #[clap(long)]
+    keys: bool,
+
+    /// Seconds typed keys stay visible after the last keystroke
+    #[clap(long, default_value = "1.0")]
+    keys_duration: f64,
+
     /// Render with window decorations
     #[clap(long)]
     window: bool,
@@ -164,9 +185,14 @@ fn main() -> Result<()> {
         idle_time_limit: cli.idle_time_limit,
         loop_enable: !cli.no_loop,
         at: cli.at,
+        transcript: cli.transcript,
+        cut_alt_screen: cli.cut_alt_screen,
         from: cli.from,
         to: cli.to,
         no_cursor: cli.no_cursor,
+        cursor_blink: cli.cursor_blink,
+        keys: cli.keys,
+        keys_duration: cli.keys_duration,
         window: cli.window,
         padding: cli.padding,
         padding_x: cli.padding_x,
@@ -191,6 +217,9 @@ fn main() -> Result<()> {
     let mut emulator = asg::terminal::Emulator::new(cols as usize, rows as usize);
     let mut frames: Vec<asg::terminal::Frame> = Vec::new();
     let mut durations: Vec<f64> = Vec::new();
+    let mut keystrokes: Vec<asg::keys::Keystroke> = Vec::new();
+    // Position on the output timeline, used to place keystrokes
+    let mut elapsed = 0.0;
     let mut last_time = config.from.unwrap_or(0.0);
     let fps = config.fps as f64;
     let trailing_default = 1.0 / fps; // show final state for at least one frame worth of time
@@ -222,20 +251,28 @@ fn main() -> Result<()> {
         .map(|s| s.contains("zsh"))
         .unwrap_or(false);
 
-    // Strip system/OSC messages by default (window title, cwd, session footer, etc.)
+    // Strip system messages by default (session footer, zsh partial-line markers).
+    // OSC sequences are left to the emulator, which tracks titles and hyperlinks.
     events.retain(|e| match e.event_type {
         asg::asciicast::EventType::Output => !should_strip_system_output(&e.data, is_zsh),
         _ => true,
     });
 
-    // Handle --at option for single frame
-    if let Some(at_time) = config.at {
+    // Handle --transcript: one tall frame with everything that was printed
+    if config.transcript {
+        emulator = emulator
+            .with_scrollback(usize::MAX)
+            .with_alternate_screen_capture(!config.cut_alt_screen);
+        for event in &events {
+            emulator.feed_event(event);
+        }
+        frames.push(emulator.transcript());
+    } else if let Some(at_time) = config.at {
+        // Handle --at option for single frame
         // Process events up to the specified time
         for event in &events {
-            if event.time <= at_time
-                && let asg::asciicast::EventType::Output = event.event_type
-            {
-                emulator.process(event.data.as_bytes());
+            if event.time <= at_time {
+                emulator.feed_event(event);
             }
         }
         frames.push(emulator.get_frame());
@@ -255,10 +292,12 @@ fn main() -> Result<()> {
                     // Show current state for the duration until this event
                     frames.push(emulator.get_frame());
                     durations.push(duration.max(0.0));
-                    // Process the event to update state
-                    if let asg::asciicast::EventType::Output = event.event_type {
-                        emulator.process(event.data.as_bytes());
+                    elapsed += duration.max(0.0);
+                    if config.keys {
+                        record_keystrokes(&mut keystrokes, &event, elapsed);
                     }
+                    // Process the event to update state
+                    emulator.feed_event(&event);
                     last_time = event.time;
                 }
                 // Add final frame showing the last state for a short trailing duration
@@ -282,9 +321,11 @@ fn main() -> Result<()> {
                             durations.push(fd);
                         }
                     }
-                    if let asg::asciicast::EventType::Output = event.event_type {
-                        emulator.process(event.data.as_bytes());
+                    elapsed += fd * frame_count as f64;
+                    if config.keys {
+                        record_keystrokes(&mut keystrokes, &event, elapsed);
                     }
+                    emulator.feed_event(&event);
                     last_time = event.time;
                 }
                 // Final frame
@@ -310,10 +351,18 @@ fn main() -> Result<()> {
         .with_theme(theme)
         .with_loop_enable(config.loop_enable)
         .with_cursor_visible(!config.no_cursor)
+        .with_cursor_blink(config.cursor_blink)
+        .with_keystrokes(keystrokes)
+        .with_keystroke_duration(config.keys_duration)
         .with_window(config.window)
+        .with_title(header.title.clone().or(header.command.clone()))
         .with_padding(config.effective_padding_x(), config.effective_padding_y());
 
-    let svg = renderer.render(&frames, &durations)?;
+    let svg = if config.transcript {
+        renderer.render_static(&frames[0])?
+    } else {
+        renderer.render(&frames, &durations)?
+    };
 
     // Write output
     let resolved_output_path = asg::input::resolve_output_path(&cli.output)?;
@@ -324,7 +373,9 @@ fn main() -> Result<()> {
     file.write_all(svg_str.as_bytes())?;
 
     println!("✨ SVG animation saved to: {}", resolved_output_path);
-    if let Some(at_time) = config.at {
+    if config.transcript {
+        println!("📜 Transcript: {} lines", frames[0].height);
+    } else if let Some(at_time) = config.at {
         println!("🖼️  Static frame at {:.2}s", at_time);
     } else {
         let total: f64 = durations.iter().copied().sum();
@@ -335,6 +386,17 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn record_keystrokes(keystrokes: &mut Vec<asg::keys::Keystroke>, event: &Event, time: f64) {
+    if event.event_type != asg::asciicast::EventType::Input {
+        return;
+    }
+    keystrokes.extend(
+        asg::keys::describe_input(&event.data)
+            .into_iter()
+            .map(|label| asg::keys::Keystroke { time, label }),
+    );
+}
+
 fn theme_from_header(h: &asg::theme::Theme) -> anyhow::Result<asg::theme::Theme> {
     // Build a comma-separated list of 18 hex colors (bg, fg, then 16 palette colors)
     fn to_hex(c: RGB8) -> String {
@@ -416,10 +478,6 @@ fn strip_ansi(s: &str) -> String {
 }
 
 fn should_strip_system_output(s: &str, is_zsh: bool) -> bool {
-    // Strip OSC sequences (Operating System Command): ESC ] ... (terminated by BEL or ST)
-    if s.starts_with("\x1b]") {
-        return true;
-    }
     // Strip common session footer lines
     let t = s.trim();
     if t == "Saving session..." || t == "completed." {
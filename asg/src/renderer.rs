@@ -1,12 +1,35 @@
 use anyhow::Result;
-use svg::Document;
 use svg::node::element::{
-    Animate, Circle, Definitions, Group, Rectangle, Style, Text as TextElement,
+    Anchor, Animate, Circle, Definitions, Group, Line, Path, Rectangle, Style, Text as TextElement,
 };
+use svg::{Document, Node};
 
-use crate::terminal::Frame;
+use rgb::RGB8;
+use std::sync::Arc;
+
+use crate::keys::{self, Keystroke};
+use crate::terminal::{Cell, Color, ColorOverrides, CursorShape, Frame, UnderlineStyle};
 use crate::theme::Theme;
 
+/// Visual attributes shared by a run of adjacent cells rendered as one `<text>`.
+#[derive(Clone, PartialEq, Eq)]
+struct StyleKey {
+    fg: (u8, u8, u8),
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: UnderlineStyle,
+    underline_color: (u8, u8, u8),
+    blink: bool,
+    hidden: bool,
+    strikethrough: bool,
+    overline: bool,
+    // Wide characters get a run of their own so the text after
+    // them starts exactly two columns later
+    wide: bool,
+    hyperlink: Option<Arc<str>>,
+}
+
 pub struct SvgRenderer {
     cols: usize,
     rows: usize,
@@ -15,9 +38,13 @@ pub struct SvgRenderer {
     theme: Theme,
     loop_enable: bool,
     cursor_visible: bool,
+    cursor_blink: bool,
     window: bool,
     padding_x: u16,
     padding_y: u16,
+    keystrokes: Vec<Keystroke>,
+    keystroke_duration: f64,
+    title: Option<String>,
 }
 
 impl SvgRenderer {
@@ -30,9 +57,13 @@ impl SvgRenderer {
             theme: Theme::default(),
             loop_enable: true,
             cursor_visible: true,
+            cursor_blink: false,
             window: false,
             padding_x: 10,
             padding_y: 10,
+            keystrokes: Vec::new(),
+            keystroke_duration: 1.0,
+            title: None,
         }
     }
 
@@ -61,6 +92,11 @@ impl SvgRenderer {
         self
     }
 
+    pub fn with_cursor_blink(mut self, cursor_blink: bool) -> Self {
+        self.cursor_blink = cursor_blink;
+        self
+    }
+
     pub fn with_window(mut self, window: bool) -> Self {
         self.window = window;
         self
@@ -72,12 +108,54 @@ impl SvgRenderer {
         self
     }
 
+    /// Shows the given keystrokes (timed on the output timeline) as an overlay.
+    pub fn with_keystrokes(mut self, keystrokes: Vec<Keystroke>) -> Self {
+        self.keystrokes = keystrokes;
+        self
+    }
+
+    /// Sets how long, in seconds, typed keys stay visible after the last keystroke.
+    pub fn with_keystroke_duration(mut self, duration: f64) -> Self {
+        self.keystroke_duration = duration;
+        self
+    }
+
+    /// Sets the window title shown while the recording hasn't set one via OSC 0/2.
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
     pub fn render(&self, frames: &[Frame], durations: &[f64]) -> Result<Document> {
+        self.render_document(frames, durations, true)
+    }
+
+    /// Renders a single frame as a static image, without any animation.
+    pub fn render_static(&self, frame: &Frame) -> Result<Document> {
+        self.render_document(std::slice::from_ref(frame), &[], false)
+    }
+
+    fn render_document(
+        &self,
+        frames: &[Frame],
+        durations: &[f64],
+        animate: bool,
+    ) -> Result<Document> {
         let char_width = self.font_size as f32 * 0.6;
         let line_height_px = self.font_size as f32 * self.line_height;
 
-        let content_width = self.cols as f32 * char_width;
-        let content_height = self.rows as f32 * line_height_px;
+        // Size the canvas to the largest geometry reached through resize events;
+        // a static frame such as a transcript is sized to itself
+        let (min_cols, min_rows) = if animate {
+            (self.cols, self.rows)
+        } else {
+            (0, 0)
+        };
+        let cols = frames.iter().map(|f| f.width).fold(min_cols, usize::max);
+        let rows = frames.iter().map(|f| f.height).fold(min_rows, usize::max);
+
+        let content_width = cols as f32 * char_width;
+        let content_height = rows as f32 * line_height_px;
 
         let width = content_width + self.padding_x as f32 * 2.0;
         let mut height = content_height + self.padding_y as f32 * 2.0;
@@ -98,7 +176,7 @@ impl SvgRenderer {
         // Title will be added via metadata if needed
 
         // Generate styles and text for all frames
-        let (styles, text_elements) = self.generate_styles_and_segments(frames, durations);
+        let (styles, text_elements) = self.generate_styles_and_segments(frames, durations, animate);
 
         // Create definitions with styles
         let defs = Definitions::new().add(Style::new(styles));
@@ -119,7 +197,7 @@ impl SvgRenderer {
 
         // Add window decorations if enabled
         if self.window {
-            let window_group = self.create_window_decorations(width, window_bar_height);
+            let window_group = self.create_window_decorations(width, frames, durations);
             doc = doc.add(window_group);
         }
 
@@ -137,6 +215,17 @@ impl SvgRenderer {
 
         doc = doc.add(frames_group);
 
+        if animate
+            && !frames.is_empty()
+            && let Some(overlay) = self.create_keystroke_overlay(content_width, content_height)
+        {
+            let overlay = overlay.set(
+                "transform",
+                format!("translate({}, {})", self.padding_x, y_offset),
+            );
+            doc = doc.add(overlay);
+        }
+
         Ok(doc)
     }
 
@@ -144,6 +233,7 @@ impl SvgRenderer {
         &self,
         frames: &[Frame],
         durations: &[f64],
+        animate: bool,
     ) -> (String, Vec<Group>) {
         let mut css = String::new();
         let mut frame_groups = Vec::new();
@@ -172,11 +262,26 @@ text {{
         // Chain animations using previous frame's end; first frame also restarts after last
         let last_anim_id = format!("f{}", frames.len() - 1);
         for (i, frame) in frames.iter().enumerate() {
-            // Each frame is a group with class 'frame' so default opacity is 0
-            let mut frame_group = Group::new().set("class", "frame");
+            // Each frame is a group with class 'frame' so default opacity is 0.
+            // A static render shows its only frame as is.
+            let mut frame_group = Group::new();
+            if animate {
+                frame_group = frame_group.set("class", "frame");
+            }
+
+            // A default background changed with OSC 11 covers the canvas background
+            if let Some(bg) = frame.colors.bg {
+                let rect = Rectangle::new()
+                    .set("x", -(self.padding_x as f32))
+                    .set("y", -(self.padding_y as f32))
+                    .set("width", "100%")
+                    .set("height", "100%")
+                    .set("fill", format!("#{:02x}{:02x}{:02x}", bg.r, bg.g, bg.b));
+                frame_group = frame_group.add(rect);
+            }
 
             for row in 0..frame.height {
-                // Find last non-space character to avoid rendering trailing whitespace
+                // Find last non-space character to avoid rendering trailing whitespace as text
                 let mut last_col_opt: Option<usize> = None;
                 for col in (0..frame.width).rev() {
                     if let Some(cell) = frame.get_cell(row, col)
@@ -187,7 +292,12 @@ text {{
                     }
                 }
 
-                if let Some(last_col) = last_col_opt {
+                // Backgrounds span the full row, so blank but colored rows still count
+                let row_has_background = (0..frame.width)
+                    .filter_map(|col| frame.get_cell(row, col))
+                    .any(has_background);
+
+                if last_col_opt.is_some() || row_has_background {
                     // Create a row group positioned at the top of the row box
                     let mut row_group = Group::new().set(
                         "transform",
@@ -196,17 +306,16 @@ text {{
 
                     // 1) Background rectangles grouped by bg color runs
                     let mut bg_group = Group::new();
-                    let theme_bg = (self.theme.bg.r, self.theme.bg.g, self.theme.bg.b);
                     let mut bg_run_start: usize = 0;
                     let mut bg_run_color: Option<(u8, u8, u8)> = None;
 
                     // (helper removed) we'll flush bg runs inline to avoid borrow issues
 
-                    for col in 0..=last_col {
+                    for col in 0..frame.width {
                         if let Some(cell) = frame.get_cell(row, col) {
-                            let bg_tuple = (cell.bg.r, cell.bg.g, cell.bg.b);
-                            // Skip painting backgrounds that match the global background or pure black default
-                            let should_paint = bg_tuple != theme_bg && bg_tuple != (0, 0, 0);
+                            let (_, bg) = self.cell_colors(cell, &frame.colors);
+                            let bg_tuple = (bg.r, bg.g, bg.b);
+                            let should_paint = has_background(cell);
                             match (bg_run_color, should_paint) {
                                 (None, true) => {
                                     bg_run_color = Some(bg_tuple);
@@ -216,7 +325,7 @@ text {{
                                     // continue run
                                 }
                                 (Some(current), _) => {
-                                    // flush and stop run
+                                    // flush, then start a new run here if this cell is painted too
                                     if col > bg_run_start {
                                         let width = (col - bg_run_start) as f32 * char_width;
                                         let x = bg_run_start as f32 * char_width;
@@ -230,7 +339,8 @@ text {{
                                             .set("fill", fill);
                                         bg_group = bg_group.add(rect);
                                     }
-                                    bg_run_color = None;
+                                    bg_run_color = should_paint.then_some(bg_tuple);
+                                    bg_run_start = col;
                                 }
                                 (None, false) => {
                                     // no-op
@@ -240,7 +350,7 @@ text {{
                     }
                     // Flush final bg run
                     if let Some(color) = bg_run_color {
-                        let end = last_col + 1;
+                        let end = frame.width;
                         if end > bg_run_start {
                             let width = (end - bg_run_start) as f32 * char_width;
                             let x = bg_run_start as f32 * char_width;
@@ -258,90 +368,68 @@ text {{
 
                     row_group = row_group.add(bg_group);
 
+                    let Some(last_col) = last_col_opt else {
+                        frame_group = frame_group.add(row_group);
+                        continue;
+                    };
+
                     // 2) Foreground text grouped by (fg color + styles)
                     let mut text_group = Group::new().set(
                         "transform",
                         format!("translate(0, {})", self.font_size as f32),
                     );
 
-                    #[derive(Clone, Copy, PartialEq, Eq)]
-                    struct StyleKey {
-                        fg: (u8, u8, u8),
-                        bold: bool,
-                        italic: bool,
-                        underline: bool,
-                    }
-
                     let mut run_text = String::new();
                     let mut run_start_col: usize = 0;
+                    let mut run_end_col: usize = 0;
                     let mut run_key: Option<StyleKey> = None;
 
-                    // (helper removed) we'll flush text runs inline
-
                     for col in 0..=last_col {
                         if let Some(cell) = frame.get_cell(row, col) {
-                            let key = StyleKey {
-                                fg: (cell.fg.r, cell.fg.g, cell.fg.b),
-                                bold: cell.bold,
-                                italic: cell.italic,
-                                underline: cell.underline,
-                            };
-                            match run_key {
-                                None => {
-                                    run_key = Some(key);
-                                    run_start_col = col;
-                                    run_text.push(cell.ch);
-                                }
-                                Some(current) if current == key => {
-                                    run_text.push(cell.ch);
-                                }
-                                Some(current) => {
-                                    // flush previous
-                                    if !run_text.is_empty() {
-                                        let x = run_start_col as f32 * char_width;
-                                        let (r, g, b) = current.fg;
-                                        let fill = format!("#{:02x}{:02x}{:02x}", r, g, b);
-                                        let mut el = TextElement::new(run_text.clone())
-                                            .set("x", x)
-                                            .set("fill", fill);
-                                        if current.bold {
-                                            el = el.set("font-weight", "bold");
-                                        }
-                                        if current.italic {
-                                            el = el.set("font-style", "italic");
-                                        }
-                                        if current.underline {
-                                            el = el.set("text-decoration", "underline");
-                                        }
-                                        text_group = text_group.add(el);
+                            if cell.width == 0 {
+                                // Placeholder following a wide character
+                                continue;
+                            }
+                            let key = self.style_key(cell, &frame.colors);
+                            match &run_key {
+                                Some(current) if *current == key && !key.wide => {}
+                                _ => {
+                                    if let Some(current) = &run_key
+                                        && !run_text.is_empty()
+                                        && !current.hidden
+                                    {
+                                        let cols = run_end_col - run_start_col;
+                                        text_group = text_group.add(self.create_text_run(
+                                            std::mem::take(&mut run_text),
+                                            run_start_col,
+                                            cols,
+                                            current,
+                                            char_width,
+                                        ));
                                     }
-                                    // start new
                                     run_key = Some(key);
                                     run_start_col = col;
                                     run_text.clear();
-                                    run_text.push(cell.ch);
                                 }
                             }
+                            run_text.push(cell.ch);
+                            run_text.extend(&cell.combining);
+                            run_end_col = col + cell.width as usize;
                         }
                     }
 
                     if let Some(current) = run_key
                         && !run_text.is_empty()
+                        && !current.hidden
                     {
-                        let x = run_start_col as f32 * char_width;
-                        let (r, g, b) = current.fg;
-                        let fill = format!("#{:02x}{:02x}{:02x}", r, g, b);
-                        let mut el = TextElement::new(run_text).set("x", x).set("fill", fill);
-                        if current.bold {
-                            el = el.set("font-weight", "bold");
-                        }
-                        if current.italic {
-                            el = el.set("font-style", "italic");
-                        }
-                        if current.underline {
-                            el = el.set("text-decoration", "underline");
-                        }
-                        text_group = text_group.add(el);
+                        let cols = run_end_col - run_start_col;
+                        text_group = text_group.add(self.create_text_run(
+                            run_text,
+                            run_start_col,
+                            cols,
+                            &current,
+                            char_width,
+                        ));
                     }
 
                     row_group = row_group.add(text_group);
@@ -349,6 +437,17 @@ text {{
                 }
             }
 
+            if self.cursor_visible
+                && let Some(cursor_group) = self.create_cursor(frame, char_width, line_height_px)
+            {
+                frame_group = frame_group.add(cursor_group);
+            }
+
+            if !animate {
+                frame_groups.push(frame_group);
+                continue;
+            }
+
             // Animate opacity for this frame's time slice; chain to previous frame's end
             let begin_attr = if i == 0 {
                 if self.loop_enable {
@@ -377,14 +476,316 @@ text {{
         (css, frame_groups)
     }
 
-    fn create_window_decorations(&self, width: f32, _height: f32) -> Group {
+    /// Builds the keystroke overlay layer: one box per overlay segment, shown
+    /// at its time relative to the start of the first frame.
+    fn create_keystroke_overlay(&self, content_width: f32, content_height: f32) -> Option<Group> {
+        let segments = keys::overlay_segments(&self.keystrokes, self.keystroke_duration);
+        if segments.is_empty() {
+            return None;
+        }
+
+        let font_size = self.font_size as f32 * 1.2;
+        let char_width = font_size * 0.6;
+        let box_height = font_size * 1.8;
+        let fg = self.theme.fg;
+        let bg = self.theme.bg;
+
+        let mut overlay = Group::new().set("class", "keys");
+        for segment in segments {
+            let dur = segment.end - segment.start;
+            if dur <= 0.0 {
+                continue;
+            }
+
+            let box_width =
+                (segment.text.chars().count() as f32 * char_width + font_size).min(content_width);
+            let x = (content_width - box_width) / 2.0;
+            let y = content_height - box_height - font_size * 0.5;
+
+            let rect = Rectangle::new()
+                .set("x", x)
+                .set("y", y)
+                .set("width", box_width)
+                .set("height", box_height)
+                .set("rx", 6)
+                .set("fill", format!("#{:02x}{:02x}{:02x}", fg.r, fg.g, fg.b))
+                .set("fill-opacity", 0.85);
+            let text = TextElement::new(segment.text)
+                .set("x", content_width / 2.0)
+                .set("y", y + box_height / 2.0)
+                .set("font-size", font_size)
+                .set("text-anchor", "middle")
+                .set("dominant-baseline", "central")
+                .set("fill", format!("#{:02x}{:02x}{:02x}", bg.r, bg.g, bg.b));
+
+            let anim = if segment.fade_out {
+                Animate::new()
+                    .set("values", "1;1;0")
+                    .set("keyTimes", "0;0.7;1")
+            } else {
+                Animate::new()
+                    .set("values", "1;1")
+                    .set("keyTimes", "0;1")
+                    .set("calcMode", "discrete")
+            };
+            let anim = anim
+                .set("attributeName", "opacity")
+                .set("begin", format!("f0.begin+{:.6}s", segment.start))
+                .set("dur", format!("{:.6}s", dur));
+
+            let group = Group::new()
+                .set("class", "frame")
+                .add(rect)
+                .add(text)
+                .add(anim);
+            overlay = overlay.add(group);
+        }
+
+        Some(overlay)
+    }
+
+    fn style_key(&self, cell: &Cell, colors: &ColorOverrides) -> StyleKey {
+        let (fg, _) = self.cell_colors(cell, colors);
+        let underline_color = match cell.underline_color {
+            Color::Default => fg,
+            color => self.resolve_fg(color, false, colors),
+        };
+        StyleKey {
+            fg: (fg.r, fg.g, fg.b),
+            bold: cell.bold,
+            dim: cell.dim,
+            italic: cell.italic,
+            underline: cell.underline,
+            underline_color: (underline_color.r, underline_color.g, underline_color.b),
+            blink: cell.blink,
+            hidden: cell.hidden,
+            strikethrough: cell.strikethrough,
+            overline: cell.overline,
+            wide: cell.width == 2,
+            hyperlink: cell.hyperlink.clone().filter(|href| is_safe_link(href)),
+        }
+    }
+
+    /// Renders a run of text spanning `cols` columns, relative to the row's
+    /// baseline, along with its decoration lines. Hidden runs (SGR 8) are
+    /// skipped by the caller so only their background is painted.
+    fn create_text_run(
+        &self,
+        text: String,
+        start_col: usize,
+        cols: usize,
+        key: &StyleKey,
+        char_width: f32,
+    ) -> Box<dyn Node> {
+        let x = start_col as f32 * char_width;
+        let width = cols as f32 * char_width;
+        let font_size = self.font_size as f32;
+        let thickness = (font_size / 14.0).max(1.0);
+        let (r, g, b) = key.fg;
+        let fill = format!("#{:02x}{:02x}{:02x}", r, g, b);
+        let (r, g, b) = key.underline_color;
+        let underline_fill = format!("#{:02x}{:02x}{:02x}", r, g, b);
+
+        let mut nodes: Vec<Box<dyn Node>> = Vec::new();
+        let mut el = TextElement::new(text).set("x", x).set("fill", fill.clone());
+        if key.bold {
+            el = el.set("font-weight", "bold");
+        }
+        if key.italic {
+            el = el.set("font-style", "italic");
+        }
+        nodes.push(Box::new(el));
+
+        let line = |y: f32, stroke: &str| {
+            Line::new()
+                .set("x1", x)
+                .set("x2", x + width)
+                .set("y1", y)
+                .set("y2", y)
+                .set("stroke", stroke.to_string())
+                .set("stroke-width", thickness)
+        };
+
+        let underline_y = font_size * 0.2;
+        match key.underline {
+            UnderlineStyle::None => {}
+            UnderlineStyle::Single => nodes.push(Box::new(line(underline_y, &underline_fill))),
+            UnderlineStyle::Double => {
+                nodes.push(Box::new(line(underline_y - thickness, &underline_fill)));
+                nodes.push(Box::new(line(underline_y + thickness, &underline_fill)));
+            }
+            UnderlineStyle::Dotted => nodes.push(Box::new(
+                line(underline_y, &underline_fill)
+                    .set("stroke-dasharray", format!("{} {}", thickness, thickness)),
+            )),
+            UnderlineStyle::Dashed => nodes.push(Box::new(line(underline_y, &underline_fill).set(
+                "stroke-dasharray",
+                format!("{} {}", thickness * 3.0, thickness * 2.0),
+            ))),
+            UnderlineStyle::Curly => {
+                // A wave made of quadratic curves, two per column
+                let amplitude = thickness * 1.5;
+                let half = char_width / 2.0;
+                let mut d = format!("M{} {}", x, underline_y);
+                for i in 0..cols * 2 {
+                    let dy = if i % 2 == 0 { -amplitude } else { amplitude };
+                    d.push_str(&format!(" q{} {} {} 0", half / 2.0, dy * 2.0, half));
+                }
+                let path = Path::new()
+                    .set("d", d)
+                    .set("fill", "none")
+                    .set("stroke", underline_fill.clone())
+                    .set("stroke-width", thickness);
+                nodes.push(Box::new(path));
+            }
+        }
+        if key.strikethrough {
+            nodes.push(Box::new(line(-font_size * 0.3, &fill)));
+        }
+        if key.overline {
+            nodes.push(Box::new(line(-font_size * 0.95, &fill)));
+        }
+
+        let node: Box<dyn Node> = if nodes.len() == 1 && !key.blink && !key.dim {
+            nodes.remove(0)
+        } else {
+            let mut group = Group::new();
+            if key.dim {
+                group = group.set("opacity", 0.5);
+            }
+            for node in nodes {
+                group = group.add(node);
+            }
+            if key.blink {
+                group = group.add(blink_animation());
+            }
+            Box::new(group)
+        };
+
+        match &key.hyperlink {
+            Some(href) => Box::new(Anchor::new().set("href", href.as_ref()).add(node)),
+            None => node,
+        }
+    }
+
+    /// Draws the frame's cursor, or returns `None` when it is hidden or off-screen.
+    fn create_cursor(&self, frame: &Frame, char_width: f32, line_height_px: f32) -> Option<Group> {
+        let cursor = &frame.cursor;
+        if !cursor.visible {
+            return None;
+        }
+        let cell = frame.get_cell(cursor.row, cursor.col)?;
+
+        let x = cursor.col as f32 * char_width;
+        let y = cursor.row as f32 * line_height_px;
+        let (fg, bg) = self.cell_colors(cell, &frame.colors);
+        let fill = format!("#{:02x}{:02x}{:02x}", fg.r, fg.g, fg.b);
+        let thickness = (self.font_size as f32 / 7.0).max(1.0);
+
+        let mut group = Group::new().set("class", "cursor");
+        match cursor.shape {
+            CursorShape::Block => {
+                let rect = Rectangle::new()
+                    .set("x", x)
+                    .set("y", y)
+                    .set("width", char_width * cell.width.max(1) as f32)
+                    .set("height", line_height_px)
+                    .set("fill", fill);
+                group = group.add(rect);
+
+                // Redraw the glyph under the cursor in the cell's background color
+                if cell.ch != ' ' && !cell.hidden {
+                    let mut el = TextElement::new(cell.grapheme())
+                        .set("x", x)
+                        .set("y", y + self.font_size as f32)
+                        .set("fill", format!("#{:02x}{:02x}{:02x}", bg.r, bg.g, bg.b));
+                    if cell.bold {
+                        el = el.set("font-weight", "bold");
+                    }
+                    if cell.italic {
+                        el = el.set("font-style", "italic");
+                    }
+                    group = group.add(el);
+                }
+            }
+            CursorShape::Underline => {
+                let rect = Rectangle::new()
+                    .set("x", x)
+                    .set("y", y + line_height_px - thickness)
+                    .set("width", char_width * cell.width.max(1) as f32)
+                    .set("height", thickness)
+                    .set("fill", fill);
+                group = group.add(rect);
+            }
+            CursorShape::Bar => {
+                let rect = Rectangle::new()
+                    .set("x", x)
+                    .set("y", y)
+                    .set("width", thickness)
+                    .set("height", line_height_px)
+                    .set("fill", fill);
+                group = group.add(rect);
+            }
+        }
+
+        if self.cursor_blink && cursor.blink {
+            group = group.add(blink_animation());
+        }
+
+        Some(group)
+    }
+
+    /// Resolves a cell's foreground and background colors, swapped for inverse video.
+    fn cell_colors(&self, cell: &Cell, colors: &ColorOverrides) -> (RGB8, RGB8) {
+        let fg = self.resolve_fg(cell.fg, cell.bold, colors);
+        let bg = self.resolve_bg(cell.bg, colors);
+        if cell.inverse { (bg, fg) } else { (fg, bg) }
+    }
+
+    /// Resolves a cell's foreground color against the theme and the recording's
+    /// color changes. Bold text in one of the eight basic colors is drawn with
+    /// its bright variant.
+    fn resolve_fg(&self, color: Color, bold: bool, colors: &ColorOverrides) -> RGB8 {
+        match color {
+            Color::Default => colors.fg.unwrap_or_else(|| self.theme.fg.into()),
+            Color::Indexed(idx) if bold && idx < 8 => self.palette_color(idx + 8, colors),
+            Color::Indexed(idx) => self.palette_color(idx, colors),
+            Color::Rgb(rgb) => rgb,
+        }
+    }
+
+    /// Resolves a cell's background color against the theme and the recording's
+    /// color changes.
+    fn resolve_bg(&self, color: Color, colors: &ColorOverrides) -> RGB8 {
+        match color {
+            Color::Default => colors.bg.unwrap_or_else(|| self.theme.bg.into()),
+            Color::Indexed(idx) => self.palette_color(idx, colors),
+            Color::Rgb(rgb) => rgb,
+        }
+    }
+
+    /// Looks up a palette entry, preferring one reprogrammed with OSC 4.
+    fn palette_color(&self, idx: u8, colors: &ColorOverrides) -> RGB8 {
+        colors
+            .palette
+            .get(&idx)
+            .copied()
+            .unwrap_or_else(|| self.theme.get_color(idx))
+    }
+
+    fn create_window_decorations(&self, width: f32, frames: &[Frame], durations: &[f64]) -> Group {
         let mut group = Group::new();
 
-        // Window bar
+        // Window bar, tinted slightly towards the foreground so it stands out
+        // from the terminal background in both dark and light themes
+        let bar_color = mix(self.theme.bg.into(), self.theme.fg.into(), 0.12);
         let bar = Rectangle::new()
             .set("width", width)
             .set("height", 30)
-            .set("fill", "#2d2d2d")
+            .set(
+                "fill",
+                format!("#{:02x}{:02x}{:02x}", bar_color.r, bar_color.g, bar_color.b),
+            )
             .set("rx", "5")
             .set("ry", "5");
         group = group.add(bar);
@@ -419,15 +820,318 @@ text {{
             .set("fill", "#28ca42");
         group = group.add(maximize);
 
-        // Window title - use Group with text
-        let title_group = Group::new()
-            .set(
-                "transform",
-                format!("translate({}, {})", width / 2.0, button_y + 5.0),
-            )
-            .add(svg::node::Text::new("Terminal"));
-        group = group.add(title_group);
+        // Window title: the live OSC title of each frame, falling back to the
+        // configured one. Frames sharing a title are shown as one segment.
+        let fg = self.theme.fg;
+        let title_text = |title: &str| {
+            TextElement::new(title)
+                .set("x", width / 2.0)
+                .set("y", button_y)
+                .set("font-size", 13)
+                .set("text-anchor", "middle")
+                .set("dominant-baseline", "central")
+                .set("fill", format!("#{:02x}{:02x}{:02x}", fg.r, fg.g, fg.b))
+        };
+        let titles: Vec<Option<&str>> = frames
+            .iter()
+            .map(|frame| frame.title.as_deref().or(self.title.as_deref()))
+            .collect();
+
+        if titles.iter().all(|title| *title == titles[0]) {
+            let title = titles.first().copied().flatten().or(self.title.as_deref());
+            if let Some(title) = title {
+                group = group.add(title_text(title));
+            }
+            return group;
+        }
+
+        let mut start = 0;
+        while start < titles.len() {
+            let end = (start..titles.len())
+                .find(|&i| titles[i] != titles[start])
+                .unwrap_or(titles.len());
+            if let Some(title) = titles[start] {
+                let dur: f64 = (start..end)
+                    .map(|i| durations.get(i).copied().unwrap_or(0.0).max(0.000_001))
+                    .sum();
+                let anim = Animate::new()
+                    .set("attributeName", "opacity")
+                    .set("begin", format!("f{}.begin", start))
+                    .set("dur", format!("{:.6}s", dur))
+                    .set("values", "1;1")
+                    .set("keyTimes", "0;1")
+                    .set("calcMode", "discrete");
+                group = group.add(
+                    Group::new()
+                        .set("class", "frame")
+                        .add(title_text(title))
+                        .add(anim),
+                );
+            }
+            start = end;
+        }
 
         group
     }
 }
+
+/// Whether a cell needs its own background rect; the default background is
+/// already painted by the canvas.
+fn has_background(cell: &Cell) -> bool {
+    cell.inverse || cell.bg != Color::Default
+}
+
+/// Whether a hyperlink target may be emitted as a link. Recordings are
+/// untrusted, so schemes such as `javascript:` are rendered as plain text.
+fn is_safe_link(href: &str) -> bool {
+    href.split_once(':').is_some_and(|(scheme, _)| {
+        ["http", "https", "mailto", "ftp", "file"]
+            .iter()
+            .any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+    })
+}
+
+/// Linearly interpolates between two colors, `t` = 0 giving `a` and 1 giving `b`.
+fn mix(a: RGB8, b: RGB8, t: f32) -> RGB8 {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    RGB8::new(channel(a.r, b.r), channel(a.g, b.g), channel(a.b, b.b))
+}
+
+/// A one-second on/off cycle used for blinking text and cursors. It toggles
+/// visibility so it doesn't interfere with opacity (dim text, frame switching).
+fn blink_animation() -> Animate {
+    Animate::new()
+        .set("attributeName", "visibility")
+        .set("values", "visible;hidden")
+        .set("keyTimes", "0;0.5")
+        .set("dur", "1s")
+        .set("repeatCount", "indefinite")
+        .set("calcMode", "discrete")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Emulator;
+
+    /// Renders the emulator's screen after `input` as a static SVG string.
+    fn render(input: &str, cols: usize, rows: usize) -> String {
+        render_with_theme(input, cols, rows, Theme::default())
+    }
+
+    fn render_with_theme(input: &str, cols: usize, rows: usize, theme: Theme) -> String {
+        let mut emulator = Emulator::new(cols, rows);
+        emulator.process_string(input);
+        SvgRenderer::new(cols, rows)
+            .with_theme(theme)
+            .with_cursor_visible(false)
+            .render_static(&emulator.get_frame())
+            .unwrap()
+            .to_string()
+    }
+
+    /// The `x`, `width` and `fill` of every background rect, in character
+    /// columns, skipping the canvas background.
+    fn background_rects(svg: &str) -> Vec<(usize, usize, String)> {
+        let char_width = 14.0 * 0.6;
+        svg.split("<rect")
+            .skip(1)
+            .filter_map(|tag| {
+                let x: f32 = attr(tag, "x")?.parse().ok()?;
+                let width: f32 = attr(tag, "width")?.parse().ok()?;
+                let cols = |v: f32| (v / char_width).round() as usize;
+                Some((cols(x), cols(width), attr(tag, "fill")?))
+            })
+            .collect()
+    }
+
+    /// The value of attribute `name` in the tag starting `tag`.
+    fn attr(tag: &str, name: &str) -> Option<String> {
+        let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+        let end = start + tag[start..].find('"')?;
+        Some(tag[start..end].to_string())
+    }
+
+    /// The `x`, `y`, `width` and `height` of the first rect in `svg`, rounded
+    /// to a tenth of a pixel.
+    fn rect_geometry(svg: &str) -> [f32; 4] {
+        let tag = &svg[svg.find("<rect").unwrap()..];
+        ["x", "y", "width", "height"].map(|name| {
+            let value: f32 = attr(tag, name).unwrap().parse().unwrap();
+            (value * 10.0).round() / 10.0
+        })
+    }
+
+    #[test]
+    fn test_adjacent_background_runs() {
+        let svg = render("\x1b[41mab\x1b[42mcd\x1b[0m\r\n\x1b[44m\x1b[K", 6, 2);
+        assert_eq!(
+            background_rects(&svg),
+            vec![
+                (0, 2, "#dd3c69".to_string()),
+                (2, 2, "#4ebf22".to_string()),
+                (0, 6, "#26b0d7".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_and_explicit_colors() {
+        // A light theme whose background is also palette entry 0
+        let theme: Theme = "ffffff,333333,ffffff,cc0000,00cc00,cccc00,0000cc,cc00cc,00cccc,eeeeee,\
+                            888888,ff0000,00ff00,ffff00,0000ff,ff00ff,00ffff,ffffff"
+            .parse()
+            .unwrap();
+        let svg = render_with_theme("plain \x1b[40mblack\x1b[0m", 12, 1, theme);
+
+        // Default text follows the theme foreground and gets no background,
+        // while the explicit color is painted even though it matches the theme
+        assert!(svg.contains(r##"<text fill="#333333" x="0">"##));
+        assert_eq!(background_rects(&svg), vec![(6, 5, "#ffffff".to_string())]);
+    }
+
+    #[test]
+    fn test_text_attributes() {
+        // Inverse over the default colors swaps them; hidden text only keeps its background
+        let svg = render("\x1b[7minv\x1b[0m \x1b[8;44msecret\x1b[0m", 12, 1);
+        assert_eq!(
+            background_rects(&svg),
+            vec![(0, 3, "#cccccc".to_string()), (4, 6, "#26b0d7".to_string())]
+        );
+        assert!(svg.contains(r##"<text fill="#121314" x="0">"##));
+        assert!(!svg.contains("secret"));
+
+        // Dim and blink apply to the whole run, decoration lines included
+        let svg = render("\x1b[2;5;9;53;4:3mx", 4, 1);
+        let group = &svg[svg.find(r#"<g opacity="0.5">"#).unwrap()..];
+        let group = &group[..group.find("</g>").unwrap()];
+        assert!(group.contains(">\nx\n</text>"));
+        assert_eq!(group.matches("<line ").count(), 2); // strikethrough and overline
+        assert_eq!(group.matches("<path ").count(), 1); // curly underline
+        assert!(group.contains(r#"<animate attributeName="visibility""#));
+
+        let underlines = |sgr: &str| {
+            let svg = render(&format!("\x1b[{sgr}mx"), 4, 1);
+            (
+                svg.matches("<line ").count(),
+                svg.matches("stroke-dasharray").count(),
+            )
+        };
+        assert_eq!(underlines("4"), (1, 0));
+        assert_eq!(underlines("21"), (2, 0));
+        assert_eq!(underlines("4:4"), (1, 1));
+        assert_eq!(underlines("4:5"), (1, 1));
+    }
+
+    #[test]
+    fn test_window_titles() {
+        let mut emulator = Emulator::new(10, 2);
+        let mut frames = vec![emulator.get_frame()];
+        for output in ["\x1b]2;vim\x07", "x", "\x1b]2;zsh\x07"] {
+            emulator.process_string(output);
+            frames.push(emulator.get_frame());
+        }
+        let renderer = SvgRenderer::new(10, 2)
+            .with_window(true)
+            .with_title(Some("demo".to_string()));
+        let svg = renderer
+            .render(&frames, &[1.0, 0.5, 0.25, 2.0])
+            .unwrap()
+            .to_string();
+
+        // Each title is shown from the frame it first appears in, for as long as it lasts
+        let segment = |title: &str| {
+            let text = svg.find(&format!(">\n{}\n</text>", title)).unwrap();
+            let anim = text + svg[text..].find("<animate").unwrap();
+            svg[anim..anim + svg[anim..].find("/>").unwrap()].to_string()
+        };
+        assert!(segment("demo").contains(r#"begin="f0.begin""#));
+        assert!(segment("demo").contains(r#"dur="1.000000s""#));
+        assert!(segment("vim").contains(r#"begin="f1.begin""#));
+        assert!(segment("vim").contains(r#"dur="0.750000s""#));
+        assert!(segment("zsh").contains(r#"begin="f3.begin""#));
+        assert!(segment("zsh").contains(r#"dur="2.000000s""#));
+
+        // Without an OSC title the configured one is shown throughout
+        let frames = vec![Frame::new(10, 2), Frame::new(10, 2)];
+        let svg = renderer.render(&frames, &[1.0, 1.0]).unwrap().to_string();
+        assert_eq!(svg.matches(">\ndemo\n</text>").count(), 1);
+        assert!(!svg.contains(r#"begin="f0.begin""#));
+
+        let svg = SvgRenderer::new(10, 2)
+            .with_window(true)
+            .render(&frames, &[1.0, 1.0])
+            .unwrap()
+            .to_string();
+        assert!(!svg.contains("font-size=\"13\""));
+    }
+
+    #[test]
+    fn test_cursor() {
+        let cursor = |input: &str| {
+            let mut emulator = Emulator::new(4, 1);
+            emulator.process_string(input);
+            let svg = SvgRenderer::new(4, 1)
+                .render_static(&emulator.get_frame())
+                .unwrap()
+                .to_string();
+            let start = svg.find(r#"<g class="cursor">"#)?;
+            Some(svg[start..start + svg[start..].find("</g>").unwrap()].to_string())
+        };
+
+        // Hidden with DECTCEM
+        assert_eq!(cursor("ab\x1b[?25l"), None);
+
+        let block = cursor("ab\x1b[D").unwrap();
+        assert_eq!(rect_geometry(&block), [8.4, 0.0, 8.4, 19.6]);
+        assert!(block.contains(">\nb\n</text>"));
+
+        let underline = cursor("\x1b[4 qab").unwrap();
+        assert_eq!(rect_geometry(&underline), [16.8, 17.6, 8.4, 2.0]);
+        assert!(!underline.contains("<text"));
+
+        let bar = cursor("\x1b[6 qab").unwrap();
+        assert_eq!(rect_geometry(&bar), [16.8, 0.0, 2.0, 19.6]);
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let svg = render(
+            "see \x1b]8;;https://x.org/?a=1&b=\"2\"\x1b\\docs\x1b]8;;\x1b\\ ok",
+            20,
+            1,
+        );
+        let anchor = r#"<a href="https://x.org/?a=1&amp;b=&quot;2&quot;">"#;
+        assert_eq!(svg.matches("<a ").count(), 1);
+        let start = svg.find(anchor).unwrap() + anchor.len();
+        let end = start + svg[start..].find("</a>").unwrap();
+        assert!(svg[start..end].contains("docs"));
+        assert!(!svg[start..end].contains("see"));
+
+        // Only well-known schemes become links
+        let svg = render(
+            "\x1b]8;;javascript:alert(document.domain)\x1b\\click\x1b]8;;\x1b\\",
+            20,
+            1,
+        );
+        assert!(!svg.contains("<a "));
+        assert!(!svg.contains("javascript"));
+        assert!(svg.contains("click"));
+    }
+
+    #[test]
+    fn test_transcript_canvas_fits_content() {
+        let mut emulator = Emulator::new(10, 10).with_scrollback(100);
+        emulator.process_string("one\r\ntwo");
+        let frame = emulator.transcript();
+        assert_eq!(frame.height, 2);
+
+        let svg = SvgRenderer::new(10, 10)
+            .render_static(&frame)
+            .unwrap()
+            .to_string();
+        // Two rows of 14px * 1.4 plus 10px padding on each side
+        assert!(svg.contains(r#"height="59.2""#));
+        assert!(svg.contains(r#"viewBox="0 0 104 59""#));
+    }
+}
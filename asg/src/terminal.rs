@@ -1,33 +1,211 @@
 use rgb::RGB8;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use unicode_width::UnicodeWidthChar;
 use vte::{Params, Parser, Perform};
 
+use crate::asciicast::{Event, EventType};
+
+/// A color reference stored in a cell, resolved against the theme at render time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Color {
+    /// The terminal's default foreground or background color.
+    #[default]
+    Default,
+    /// An entry of the 256-color palette (0-15 come from the theme).
+    Indexed(u8),
+    /// A 24-bit truecolor value.
+    Rgb(RGB8),
+}
+
+/// Underline style selected by SGR 4 (with `4:x` sub-parameters) and SGR 21.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnderlineStyle {
+    #[default]
+    None,
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Cell {
     pub ch: char,
-    pub fg: RGB8,
-    pub bg: RGB8,
+    /// Number of columns the glyph occupies: 1 for regular cells, 2 for wide
+    /// (CJK, emoji) characters, and 0 for the placeholder cell that follows a
+    /// wide character.
+    pub width: u8,
+    /// Zero-width code points that complete the grapheme cluster started by
+    /// `ch`: combining marks, variation selectors, emoji modifiers and
+    /// ZWJ-joined characters
+    pub combining: Vec<char>,
+    pub fg: Color,
+    pub bg: Color,
+    /// Underline color; `Color::Default` follows the foreground
+    pub underline_color: Color,
     pub bold: bool,
+    pub dim: bool,
     pub italic: bool,
-    pub underline: bool,
+    pub underline: UnderlineStyle,
+    pub blink: bool,
+    pub inverse: bool,
+    pub hidden: bool,
+    pub strikethrough: bool,
+    pub overline: bool,
+    /// Target of the OSC 8 hyperlink the cell belongs to
+    pub hyperlink: Option<Arc<str>>,
 }
 
 impl Default for Cell {
     fn default() -> Self {
         Cell {
             ch: ' ',
-            fg: RGB8::new(204, 204, 204),
-            bg: RGB8::new(0, 0, 0),
+            width: 1,
+            combining: Vec::new(),
+            fg: Color::Default,
+            bg: Color::Default,
+            underline_color: Color::Default,
             bold: false,
+            dim: false,
             italic: false,
-            underline: false,
+            underline: UnderlineStyle::None,
+            blink: false,
+            inverse: false,
+            hidden: false,
+            strikethrough: false,
+            overline: false,
+            hyperlink: None,
         }
     }
 }
 
+/// Character set designated into one of G0-G3 with `ESC ( ) * +`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    #[default]
+    Ascii,
+    /// DEC Special Graphics (`ESC ( 0`), the VT100 line-drawing set
+    DecSpecialGraphics,
+    /// United Kingdom (`ESC ( A`), which only replaces `#` with `£`
+    Uk,
+}
+
+impl Charset {
+    /// Maps a printed character through this character set.
+    pub fn map(self, c: char) -> char {
+        match self {
+            Charset::Ascii => c,
+            Charset::Uk => match c {
+                '#' => '£',
+                c => c,
+            },
+            Charset::DecSpecialGraphics => match c {
+                '_' => ' ',
+                '`' => '◆',
+                'a' => '▒',
+                'b' => '␉',
+                'c' => '␌',
+                'd' => '␍',
+                'e' => '␊',
+                'f' => '°',
+                'g' => '±',
+                'h' => '␤',
+                'i' => '␋',
+                'j' => '┘',
+                'k' => '┐',
+                'l' => '┌',
+                'm' => '└',
+                'n' => '┼',
+                'o' => '⎺',
+                'p' => '⎻',
+                'q' => '─',
+                'r' => '⎼',
+                's' => '⎽',
+                't' => '├',
+                'u' => '┤',
+                'v' => '┴',
+                'w' => '┬',
+                'x' => '│',
+                'y' => '≤',
+                'z' => '≥',
+                '{' => 'π',
+                '|' => '≠',
+                '}' => '£',
+                '~' => '·',
+                c => c,
+            },
+        }
+    }
+}
+
+/// Cursor shape as selected by DECSCUSR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Underline,
+    Bar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub row: usize,
+    pub col: usize,
+    pub visible: bool,
+    pub shape: CursorShape,
+    pub blink: bool,
+}
+
+impl Cursor {
+    pub fn new() -> Self {
+        Cursor {
+            row: 0,
+            col: 0,
+            visible: true,
+            shape: CursorShape::Block,
+            blink: true,
+        }
+    }
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Colors reprogrammed at runtime with OSC 4/10/11. They take precedence over
+/// the renderer's theme for the rest of the recording.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColorOverrides {
+    /// Palette entries set by OSC 4, keyed by color index
+    pub palette: HashMap<u8, RGB8>,
+    /// Default foreground set by OSC 10
+    pub fg: Option<RGB8>,
+    /// Default background set by OSC 11
+    pub bg: Option<RGB8>,
+}
+
+impl Cell {
+    /// The full grapheme cluster displayed in this cell.
+    pub fn grapheme(&self) -> String {
+        std::iter::once(self.ch)
+            .chain(self.combining.iter().copied())
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Frame {
     pub width: usize,
     pub height: usize,
+    pub cursor: Cursor,
+    /// Window title set by OSC 0/2 at the time of the snapshot
+    pub title: Option<Arc<str>>,
+    /// Palette and default color changes in effect at the time of the snapshot
+    pub colors: Arc<ColorOverrides>,
     cells: Vec<Vec<Cell>>,
 }
 
@@ -40,6 +218,9 @@ impl Frame {
         Frame {
             width,
             height,
+            cursor: Cursor::new(),
+            title: None,
+            colors: Arc::default(),
             cells,
         }
     }
@@ -52,6 +233,25 @@ impl Frame {
         self.cells.get_mut(row).and_then(|r| r.get_mut(col))
     }
 
+    /// Changes the grid geometry, truncating or padding rows at the bottom
+    /// and columns at the right. A wide character cut in half is blanked.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.cells.resize(height, vec![Cell::default(); width]);
+        for row in &mut self.cells {
+            row.resize(width, Cell::default());
+            if let Some(cell) = row.last_mut()
+                && cell.width == 2
+            {
+                *cell = Cell {
+                    bg: cell.bg,
+                    ..Cell::default()
+                };
+            }
+        }
+        self.width = width;
+        self.height = height;
+    }
+
     pub fn clear(&mut self) {
         for row in &mut self.cells {
             for cell in row {
@@ -61,45 +261,127 @@ impl Frame {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Cursor {
+/// State saved by DECSC (`ESC 7`) and restored by DECRC (`ESC 8`).
+#[derive(Debug, Clone, Default)]
+struct SavedCursor {
     row: usize,
     col: usize,
-}
-
-impl Cursor {
-    pub fn new() -> Self {
-        Cursor { row: 0, col: 0 }
-    }
+    wrap_pending: bool,
+    pen: Cell,
+    charsets: [Charset; 4],
+    gl: usize,
+    origin_mode: bool,
 }
 
 pub struct Emulator {
+    /// The grid currently displayed (primary or alternate screen)
     pub grid: Grid,
+    /// The grid not currently displayed: the alternate screen while the
+    /// primary one is active, and vice versa
+    inactive_grid: Grid,
+    alternate_screen: bool,
+    /// Top and bottom margins of the scroll region (inclusive rows)
+    scroll_top: usize,
+    scroll_bottom: usize,
     cursor: Cursor,
-    saved_cursor: Option<Cursor>,
-    pub fg_color: RGB8,
-    pub bg_color: RGB8,
-    pub bold: bool,
-    pub italic: bool,
-    pub underline: bool,
+    /// Set after writing into the last column; the wrap happens only when the
+    /// next printable character arrives (xterm's last-column behavior)
+    wrap_pending: bool,
+    /// DECAWM - whether printing past the last column wraps to the next line
+    autowrap: bool,
+    /// DECOM - whether cursor addressing is relative to the scroll region
+    origin_mode: bool,
+    /// IRM - whether printed characters shift the rest of the line right
+    insert_mode: bool,
+    saved_cursor: Option<SavedCursor>,
+    /// Colors and attributes applied to newly written cells (set by SGR)
+    pub pen: Cell,
+    /// Window title set by OSC 0/2
+    title: Option<Arc<str>>,
+    colors: Arc<ColorOverrides>,
+    /// Character sets designated into G0-G3
+    charsets: [Charset; 4],
+    /// Which of G0-G3 is invoked into GL (SI/SO, LS2/LS3)
+    gl: usize,
+    /// Set by SS2/SS3 to use G2/G3 for the next printed character only
+    single_shift: Option<usize>,
+    /// Last printed character, repeated by REP
+    last_char: Option<char>,
+    /// Tab stops, one flag per column
+    tabs: Vec<bool>,
+    /// Lines scrolled off the top of the primary screen, oldest first
+    scrollback: VecDeque<Vec<Cell>>,
+    /// Maximum number of scrollback lines kept; 0 disables scrollback
+    scrollback_limit: usize,
+    /// Number of lines ever added to the scrollback, which gives every line
+    /// a stable index across the scrollback and the screen
+    scrollback_total: usize,
+    /// Whether alternate screen episodes are recorded for the transcript
+    capture_alternate_screens: bool,
+    /// Final contents of each alternate screen episode, placed after the
+    /// line (by stable index) the cursor was on when the episode began
+    alternate_screens: Vec<(usize, Vec<Vec<Cell>>)>,
+    alternate_screen_anchor: usize,
     parser: Parser,
 }
 
 impl Emulator {
+    /// Creates an emulator; zero dimensions are treated as 1, as in `resize`.
     pub fn new(width: usize, height: usize) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
         Emulator {
             grid: Grid::new(width, height),
+            inactive_grid: Grid::new(width, height),
+            alternate_screen: false,
+            scroll_top: 0,
+            scroll_bottom: height - 1,
             cursor: Cursor::new(),
+            wrap_pending: false,
+            autowrap: true,
+            origin_mode: false,
+            insert_mode: false,
             saved_cursor: None,
-            fg_color: RGB8::new(204, 204, 204),
-            bg_color: RGB8::new(0, 0, 0),
-            bold: false,
-            italic: false,
-            underline: false,
+            pen: Cell::default(),
+            title: None,
+            colors: Arc::default(),
+            charsets: [Charset::Ascii; 4],
+            gl: 0,
+            single_shift: None,
+            last_char: None,
+            tabs: (0..width).map(is_default_tab_stop).collect(),
+            scrollback: VecDeque::new(),
+            scrollback_limit: 0,
+            scrollback_total: 0,
+            capture_alternate_screens: false,
+            alternate_screens: Vec::new(),
+            alternate_screen_anchor: 0,
             parser: Parser::new(),
         }
     }
 
+    /// Keeps up to `limit` lines that scroll off the top of the primary
+    /// screen. Scrollback is disabled by default.
+    pub fn with_scrollback(mut self, limit: usize) -> Self {
+        self.scrollback_limit = limit;
+        self.trim_scrollback();
+        self
+    }
+
+    /// Records the final contents of each alternate screen episode
+    /// (full-screen programs such as editors and pagers) so that
+    /// [`Emulator::transcript`] can show them inline.
+    pub fn with_alternate_screen_capture(mut self, enable: bool) -> Self {
+        self.capture_alternate_screens = enable;
+        self
+    }
+
+    /// Lines that scrolled off the top of the primary screen, oldest first.
+    /// Each line keeps the width the screen had when it scrolled away.
+    pub fn scrollback(&self) -> &VecDeque<Vec<Cell>> {
+        &self.scrollback
+    }
+
     pub fn process(&mut self, input: &[u8]) {
         let mut parser = std::mem::take(&mut self.parser);
         parser.advance(self, input);
@@ -110,140 +392,631 @@ impl Emulator {
         self.process(data.as_bytes());
     }
 
+    /// Applies a recorded event: output is fed to the parser and resize events
+    /// change the terminal geometry. Other event types leave the screen as is.
+    pub fn feed_event(&mut self, event: &Event) {
+        match event.event_type {
+            EventType::Output => self.process_string(&event.data),
+            EventType::Resize => match event.resize_dimensions() {
+                Some((width, height)) => self.resize(width, height),
+                None => log::warn!("Invalid resize event data: {}", event.data),
+            },
+            _ => {}
+        }
+    }
+
+    /// Resizes the terminal. When shrinking below the cursor row, lines are
+    /// dropped from the top so the cursor line stays on screen. While the
+    /// alternate screen is active, the primary screen does the same for the
+    /// cursor row saved on entering it.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        if self.cursor.row >= height {
+            let overflow = self.cursor.row + 1 - height;
+            let lines: Vec<Vec<Cell>> = self.grid.cells.drain(..overflow).collect();
+            if !self.alternate_screen {
+                self.push_scrollback(lines);
+            }
+            self.cursor.row -= overflow;
+        }
+        if self.alternate_screen
+            && let Some(saved) = &mut self.saved_cursor
+            && saved.row >= height
+        {
+            let overflow = saved.row + 1 - height;
+            saved.row -= overflow;
+            let lines: Vec<Vec<Cell>> = self.inactive_grid.cells.drain(..overflow).collect();
+            self.push_scrollback(lines);
+        }
+        self.grid.resize(width, height);
+        self.inactive_grid.resize(width, height);
+        self.scroll_top = 0;
+        self.scroll_bottom = height - 1;
+
+        // New columns get the default stops; existing ones keep theirs
+        let old_width = self.tabs.len();
+        self.tabs.truncate(width);
+        self.tabs
+            .extend((old_width..width).map(is_default_tab_stop));
+
+        self.cursor.col = self.cursor.col.min(width - 1);
+        self.wrap_pending = false;
+        if let Some(saved) = &mut self.saved_cursor {
+            saved.row = saved.row.min(height - 1);
+            saved.col = saved.col.min(width - 1);
+        }
+    }
+
+    /// Builds one tall frame holding the whole session: the scrollback
+    /// followed by the primary screen, with trailing blank lines trimmed.
+    /// Captured alternate screen episodes are spliced in after the line
+    /// that started them.
+    pub fn transcript(&self) -> Frame {
+        let screen = if self.alternate_screen {
+            &self.inactive_grid
+        } else {
+            &self.grid
+        };
+
+        let first = self.scrollback_total - self.scrollback.len();
+        let mut episodes = self
+            .alternate_screens
+            .iter()
+            .filter(|(anchor, _)| *anchor >= first)
+            .peekable();
+        let mut lines: Vec<Vec<Cell>> = Vec::new();
+        // Where the episode still in progress goes, past any earlier ones
+        let mut splice_at = 0;
+        for (i, line) in self.scrollback.iter().chain(&screen.cells).enumerate() {
+            lines.push(line.clone());
+            while let Some((_, episode)) = episodes.next_if(|(anchor, _)| *anchor == first + i) {
+                lines.extend(episode.iter().cloned());
+            }
+            if first + i == self.alternate_screen_anchor {
+                splice_at = lines.len();
+            }
+        }
+        if self.alternate_screen && self.capture_alternate_screens {
+            let trailing = lines.split_off(splice_at);
+            lines.extend(trim_blank_lines(&self.grid.cells).iter().cloned());
+            lines.extend(trailing);
+        }
+
+        let keep = lines
+            .iter()
+            .rposition(|line| !is_blank_line(line))
+            .map_or(1, |last| last + 1);
+        lines.truncate(keep);
+
+        // Scrollback lines keep the width they had, so pad to the widest one
+        let width = lines.iter().map(Vec::len).fold(screen.width, usize::max);
+        for line in &mut lines {
+            line.resize(width, Cell::default());
+        }
+
+        let mut frame = Frame::new(width, lines.len());
+        frame.cells = lines;
+        frame.cursor.visible = false;
+        frame.title = self.title.clone();
+        frame.colors = self.colors.clone();
+        frame
+    }
+
     pub fn get_frame(&self) -> Frame {
-        self.grid.clone()
+        let mut frame = self.grid.clone();
+        frame.cursor = self.cursor.clone();
+        frame.title = self.title.clone();
+        frame.colors = self.colors.clone();
+        frame
     }
 
     fn write_char(&mut self, ch: char) {
-        if let Some(cell) = self.grid.get_cell_mut(self.cursor.row, self.cursor.col) {
-            cell.ch = ch;
-            cell.fg = self.fg_color;
-            cell.bg = self.bg_color;
-            cell.bold = self.bold;
-            cell.italic = self.italic;
-            cell.underline = self.underline;
-        }
+        let width = ch.width().unwrap_or(1).clamp(1, 2).min(self.grid.width);
 
-        self.cursor.col += 1;
-        if self.cursor.col >= self.grid.width {
+        if self.wrap_pending {
+            self.wrap_pending = false;
             self.cursor.col = 0;
+            self.linefeed();
+        }
+
+        // A wide character that doesn't fit on the line wraps to the next one
+        if self.cursor.col + width > self.grid.width {
+            if self.autowrap {
+                self.cursor.col = 0;
+                self.linefeed();
+            } else {
+                self.cursor.col = self.grid.width - width;
+            }
+        }
+
+        if self.insert_mode {
+            self.insert_blanks(width);
+        }
+
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        for c in col..col + width {
+            self.clear_wide_pair(row, c);
+        }
+
+        let template = Cell {
+            ch,
+            width: width as u8,
+            combining: Vec::new(),
+            ..self.pen.clone()
+        };
+        if width == 2
+            && let Some(cell) = self.grid.get_cell_mut(row, col + 1)
+        {
+            *cell = Cell {
+                ch: ' ',
+                width: 0,
+                ..template.clone()
+            };
+        }
+        if let Some(cell) = self.grid.get_cell_mut(row, col) {
+            *cell = template;
+        }
+
+        // Stay on the last column; wrap only if another character follows
+        if col + width >= self.grid.width {
+            self.cursor.col = self.grid.width - 1;
+            self.wrap_pending = self.autowrap;
+        } else {
+            self.cursor.col = col + width;
+        }
+    }
+
+    /// Attaches `ch` to the grapheme cluster in the cell before the cursor if
+    /// it continues that cluster. Returns false when `ch` starts a new cell.
+    fn append_to_grapheme(&mut self, ch: char) -> bool {
+        let Some((row, col)) = self.previous_cell_position() else {
+            return false;
+        };
+        let Some(cell) = self.grid.get_cell_mut(row, col) else {
+            return false;
+        };
+
+        let joins = match cell.combining.last() {
+            // Anything directly after a zero-width joiner is part of the sequence
+            Some('\u{200D}') => true,
+            _ => {
+                ch.width() == Some(0)
+                    || is_emoji_modifier(ch)
+                    || (is_regional_indicator(ch)
+                        && is_regional_indicator(cell.ch)
+                        && cell.combining.is_empty())
+            }
+        };
+        if joins {
+            cell.combining.push(ch);
+        }
+        joins
+    }
+
+    /// Position of the cell holding the last character written before the cursor.
+    fn previous_cell_position(&self) -> Option<(usize, usize)> {
+        let col = if self.wrap_pending {
+            self.cursor.col
+        } else {
+            self.cursor.col.checked_sub(1)?
+        };
+        let row = self.cursor.row;
+        match self.grid.get_cell(row, col)?.width {
+            0 => Some((row, col.checked_sub(1)?)),
+            _ => Some((row, col)),
+        }
+    }
+
+    /// Before overwriting the cell at (row, col), blanks the other half of a
+    /// wide character it belongs to so no orphaned half is left behind.
+    fn clear_wide_pair(&mut self, row: usize, col: usize) {
+        let other = match self.grid.get_cell(row, col).map(|c| c.width) {
+            Some(0) if col > 0 => col - 1,
+            Some(2) => col + 1,
+            _ => return,
+        };
+        if let Some(cell) = self.grid.get_cell_mut(row, other) {
+            *cell = Cell {
+                bg: cell.bg,
+                ..Cell::default()
+            };
+        }
+    }
+
+    /// Blanks both halves of a wide character occupying (row, col), used
+    /// before shifting or erasing part of a line so it isn't split in two.
+    fn clear_wide_char(&mut self, row: usize, col: usize) {
+        self.clear_wide_pair(row, col);
+        if let Some(cell) = self.grid.get_cell_mut(row, col)
+            && cell.width != 1
+        {
+            *cell = Cell {
+                bg: cell.bg,
+                ..Cell::default()
+            };
+        }
+    }
+
+    /// Moves the cursor down one line, scrolling the scroll region when the
+    /// cursor sits on its bottom margin.
+    fn linefeed(&mut self) {
+        if self.cursor.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.grid.height {
             self.cursor.row += 1;
-            if self.cursor.row >= self.grid.height {
-                self.cursor.row = self.grid.height - 1;
-                // Scroll up
-                self.grid.cells.remove(0);
-                self.grid.cells.push(vec![Cell::default(); self.grid.width]);
+        }
+    }
+
+    /// Moves the cursor up one line, scrolling the scroll region down when the
+    /// cursor sits on its top margin.
+    fn reverse_index(&mut self) {
+        if self.cursor.row == self.scroll_top {
+            self.scroll_down(1);
+        } else if self.cursor.row > 0 {
+            self.cursor.row -= 1;
+        }
+    }
+
+    /// Scrolls the scroll region up by `n` lines, adding blank lines at the bottom.
+    fn scroll_up(&mut self, n: usize) {
+        // Only lines leaving the top of the primary screen go to the scrollback,
+        // not those scrolled out of a region further down or on the alternate screen
+        if self.scrollback_limit > 0 && self.scroll_top == 0 && !self.alternate_screen {
+            let n = n.min(self.scroll_bottom + 1);
+            let lines = self.grid.cells[..n].to_vec();
+            self.push_scrollback(lines);
+        }
+        self.scroll_lines_up(self.scroll_top, n);
+    }
+
+    fn push_scrollback(&mut self, lines: Vec<Vec<Cell>>) {
+        self.scrollback_total += lines.len();
+        self.scrollback.extend(lines);
+        self.trim_scrollback();
+    }
+
+    fn trim_scrollback(&mut self) {
+        let excess = self.scrollback.len().saturating_sub(self.scrollback_limit);
+        self.scrollback.drain(..excess);
+    }
+
+    /// Scrolls the scroll region down by `n` lines, adding blank lines at the top.
+    fn scroll_down(&mut self, n: usize) {
+        self.scroll_lines_down(self.scroll_top, n);
+    }
+
+    /// Shifts rows `top..=scroll_bottom` up by `n`, filling the bottom with blank lines.
+    fn scroll_lines_up(&mut self, top: usize, n: usize) {
+        let blank = self.blank_cell();
+        let region = &mut self.grid.cells[top..=self.scroll_bottom];
+        let n = n.min(region.len());
+        region.rotate_left(n);
+        let len = region.len();
+        for row in &mut region[len - n..] {
+            row.fill(blank.clone());
+        }
+    }
+
+    /// Shifts rows `top..=scroll_bottom` down by `n`, filling the top with blank lines.
+    fn scroll_lines_down(&mut self, top: usize, n: usize) {
+        let blank = self.blank_cell();
+        let region = &mut self.grid.cells[top..=self.scroll_bottom];
+        let n = n.min(region.len());
+        region.rotate_right(n);
+        for row in &mut region[..n] {
+            row.fill(blank.clone());
+        }
+    }
+
+    /// A blank cell carrying the current background color, used when erasing,
+    /// inserting or scrolling in new cells (background color erase).
+    fn blank_cell(&self) -> Cell {
+        Cell {
+            bg: self.pen.bg,
+            ..Cell::default()
+        }
+    }
+
+    /// Whether the alternate screen buffer is active.
+    pub fn is_alternate_screen(&self) -> bool {
+        self.alternate_screen
+    }
+
+    /// Switches between the primary and alternate screen buffers.
+    fn switch_screen(&mut self, alternate: bool) {
+        if self.capture_alternate_screens && self.alternate_screen != alternate {
+            if alternate {
+                self.alternate_screen_anchor = self.scrollback_total + self.cursor.row;
+            } else {
+                let lines = trim_blank_lines(&self.grid.cells).to_vec();
+                self.alternate_screens
+                    .push((self.alternate_screen_anchor, lines));
             }
         }
+        if self.alternate_screen != alternate {
+            std::mem::swap(&mut self.grid, &mut self.inactive_grid);
+            self.alternate_screen = alternate;
+        }
     }
 
-    fn parse_sgr_params(&mut self, params: &Params) {
-        // Flatten params for easier lookahead parsing
-        let mut vals: Vec<u16> = Vec::new();
-        for p in params {
-            for v in p {
-                vals.push(*v);
+    /// DECSC - saves the cursor position along with the pen, the character
+    /// sets and origin mode.
+    fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            row: self.cursor.row,
+            col: self.cursor.col,
+            wrap_pending: self.wrap_pending,
+            pen: self.pen.clone(),
+            charsets: self.charsets,
+            gl: self.gl,
+            origin_mode: self.origin_mode,
+        });
+    }
+
+    /// DECRC - restores the state saved by DECSC, or resets it to the
+    /// defaults (home position, plain pen) when nothing was saved.
+    fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor.clone().unwrap_or_default();
+        self.cursor.row = saved.row.min(self.grid.height - 1);
+        self.cursor.col = saved.col.min(self.grid.width - 1);
+        self.wrap_pending = saved.wrap_pending && self.cursor.col == self.grid.width - 1;
+        self.pen = saved.pen;
+        self.charsets = saved.charsets;
+        self.gl = saved.gl;
+        self.origin_mode = saved.origin_mode;
+    }
+
+    /// Moves the cursor to a 0-based position. In origin mode the row is
+    /// relative to the scroll region and confined to it.
+    fn goto(&mut self, row: usize, col: usize) {
+        let (top, bottom) = if self.origin_mode {
+            (self.scroll_top, self.scroll_bottom)
+        } else {
+            (0, self.grid.height - 1)
+        };
+        self.cursor.row = (top + row).min(bottom);
+        self.cursor.col = col.min(self.grid.width - 1);
+    }
+
+    /// Moves the cursor up `n` rows, stopping at the top margin when it
+    /// starts inside the scroll region.
+    fn cursor_up(&mut self, n: usize) {
+        let top = if self.cursor.row >= self.scroll_top {
+            self.scroll_top
+        } else {
+            0
+        };
+        self.cursor.row = self.cursor.row.saturating_sub(n).max(top);
+    }
+
+    /// Moves the cursor down `n` rows, stopping at the bottom margin when it
+    /// starts inside the scroll region.
+    fn cursor_down(&mut self, n: usize) {
+        let bottom = if self.cursor.row <= self.scroll_bottom {
+            self.scroll_bottom
+        } else {
+            self.grid.height - 1
+        };
+        self.cursor.row = (self.cursor.row + n).min(bottom);
+    }
+
+    /// CHT - moves the cursor forward `n` tab stops, or to the last column
+    /// when there are no more.
+    fn tab_forward(&mut self, n: usize) {
+        for _ in 0..n {
+            self.cursor.col = (self.cursor.col + 1..self.grid.width)
+                .find(|&col| self.tabs[col])
+                .unwrap_or(self.grid.width - 1);
+        }
+    }
+
+    /// CBT - moves the cursor back `n` tab stops, or to the first column
+    /// when there are no more.
+    fn tab_backward(&mut self, n: usize) {
+        for _ in 0..n {
+            self.cursor.col = (0..self.cursor.col)
+                .rev()
+                .find(|&col| self.tabs[col])
+                .unwrap_or(0);
+        }
+    }
+
+    /// Inserts `n` blank cells at the cursor, shifting the rest of the line
+    /// right and dropping what falls off the edge.
+    fn insert_blanks(&mut self, n: usize) {
+        let blank = self.blank_cell();
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        let n = n.min(self.grid.width - col);
+        // Don't split wide characters at the cursor or at the right edge,
+        // where the shifted line is cut off
+        self.clear_wide_char(row, col);
+        self.clear_wide_char(row, self.grid.width - n);
+        if let Some(line) = self.grid.cells.get_mut(row) {
+            let tail = &mut line[col..];
+            let n = n.min(tail.len());
+            tail.rotate_right(n);
+            tail[..n].fill(blank);
+        }
+    }
+
+    /// DECSTR - soft terminal reset. Modes, margins, the pen and the
+    /// character sets return to their defaults; the screen is left as is.
+    fn soft_reset(&mut self) {
+        self.cursor.visible = true;
+        self.autowrap = true;
+        self.origin_mode = false;
+        self.insert_mode = false;
+        self.scroll_top = 0;
+        self.scroll_bottom = self.grid.height - 1;
+        self.pen = Cell::default();
+        self.charsets = [Charset::Ascii; 4];
+        self.gl = 0;
+        self.single_shift = None;
+        self.saved_cursor = None;
+    }
+
+    /// RIS - full reset to the initial state, keeping only the geometry,
+    /// the window title and the history.
+    fn full_reset(&mut self) {
+        let mut reset = Emulator::new(self.grid.width, self.grid.height)
+            .with_scrollback(self.scrollback_limit)
+            .with_alternate_screen_capture(self.capture_alternate_screens);
+        reset.title = self.title.take();
+        reset.scrollback = std::mem::take(&mut self.scrollback);
+        reset.scrollback_total = self.scrollback_total;
+        reset.alternate_screens = std::mem::take(&mut self.alternate_screens);
+        reset.parser = std::mem::take(&mut self.parser);
+        *self = reset;
+    }
+
+    /// SM / RM - ANSI (non-private) modes.
+    fn set_mode(&mut self, mode: u16, enable: bool) {
+        match mode {
+            4 => {
+                // IRM - Insert/replace mode
+                self.insert_mode = enable;
+            }
+            _ => {
+                log::debug!("Unhandled mode: {} ({})", mode, enable);
+            }
+        }
+    }
+
+    fn set_private_mode(&mut self, mode: u16, enable: bool) {
+        match mode {
+            6 => {
+                // DECOM - Origin mode; the cursor moves to the new home position
+                self.origin_mode = enable;
+                self.goto(0, 0);
+            }
+            7 => {
+                // DECAWM - Autowrap mode
+                self.autowrap = enable;
+                if !enable {
+                    self.wrap_pending = false;
+                }
+            }
+            25 => {
+                // DECTCEM - Show/hide cursor
+                self.cursor.visible = enable;
+            }
+            47 => {
+                // Switch to/from the alternate screen
+                self.switch_screen(enable);
+            }
+            1047 => {
+                // Alternate screen, cleared when leaving it
+                let leaving = !enable && self.alternate_screen;
+                self.switch_screen(enable);
+                if leaving {
+                    self.inactive_grid.clear();
+                }
+            }
+            1048 => {
+                // Save/restore cursor as in DECSC/DECRC
+                if enable {
+                    self.save_cursor();
+                } else {
+                    self.restore_cursor();
+                }
+            }
+            1049 => {
+                // Save cursor and switch to a cleared alternate screen; restore on exit
+                if enable {
+                    self.save_cursor();
+                    self.switch_screen(true);
+                    self.grid.clear();
+                } else {
+                    self.switch_screen(false);
+                    self.restore_cursor();
+                }
+            }
+            _ => {
+                log::debug!("Unhandled private mode: {} ({})", mode, enable);
             }
         }
+    }
+
+    fn parse_sgr_params(&mut self, params: &Params) {
+        let groups: Vec<&[u16]> = params.iter().collect();
 
-        if vals.is_empty() {
+        if groups.is_empty() {
             // SGR with no params is equivalent to reset
-            self.fg_color = RGB8::new(204, 204, 204);
-            self.bg_color = RGB8::new(0, 0, 0);
-            self.bold = false;
-            self.italic = false;
-            self.underline = false;
+            self.pen = Cell::default();
             return;
         }
 
         let mut i = 0usize;
-        while i < vals.len() {
-            let v = vals[i];
+        while i < groups.len() {
+            let group = groups[i];
+            let v = group[0];
+            let pen = &mut self.pen;
             match v {
                 0 => {
-                    self.fg_color = RGB8::new(204, 204, 204);
-                    self.bg_color = RGB8::new(0, 0, 0);
-                    self.bold = false;
-                    self.italic = false;
-                    self.underline = false;
-                }
-                1 => self.bold = true,
-                3 => self.italic = true,
-                4 => self.underline = true,
-                22 => self.bold = false,
-                23 => self.italic = false,
-                24 => self.underline = false,
-                30..=37 => {
-                    self.fg_color = ansi_color_to_rgb(v - 30, self.bold);
-                }
-                39 => {
-                    // default foreground
-                    self.fg_color = RGB8::new(204, 204, 204);
-                }
-                40..=47 => {
-                    self.bg_color = ansi_color_to_rgb(v - 40, false);
-                }
-                49 => {
-                    // default background
-                    self.bg_color = RGB8::new(0, 0, 0);
+                    // SGR reset leaves the active hyperlink alone
+                    *pen = Cell {
+                        hyperlink: pen.hyperlink.take(),
+                        ..Cell::default()
+                    }
                 }
-                90..=97 => {
-                    // Bright foreground colors
-                    self.fg_color = ansi_color_to_rgb(v - 90, true);
+                1 => pen.bold = true,
+                2 => pen.dim = true,
+                3 => pen.italic = true,
+                4 => {
+                    // 4:x selects the underline style
+                    pen.underline = match group.get(1) {
+                        Some(0) => UnderlineStyle::None,
+                        Some(2) => UnderlineStyle::Double,
+                        Some(3) => UnderlineStyle::Curly,
+                        Some(4) => UnderlineStyle::Dotted,
+                        Some(5) => UnderlineStyle::Dashed,
+                        _ => UnderlineStyle::Single,
+                    };
                 }
-                100..=107 => {
-                    // Bright background colors
-                    self.bg_color = ansi_color_to_rgb(v - 100, true);
+                5 | 6 => pen.blink = true,
+                7 => pen.inverse = true,
+                8 => pen.hidden = true,
+                9 => pen.strikethrough = true,
+                21 => pen.underline = UnderlineStyle::Double,
+                22 => {
+                    pen.bold = false;
+                    pen.dim = false;
                 }
-                38 => {
-                    // Extended foreground color
-                    if i + 1 < vals.len() {
-                        match vals[i + 1] {
-                            5 => {
-                                // 256-color: 38;5;N
-                                if i + 2 < vals.len() {
-                                    let idx = vals[i + 2];
-                                    self.fg_color = color_from_256(idx);
-                                    i += 2;
-                                }
-                            }
-                            2 => {
-                                // truecolor: 38;2;R;G;B
-                                if i + 4 < vals.len() {
-                                    let r = vals[i + 2] as u8;
-                                    let g = vals[i + 3] as u8;
-                                    let b = vals[i + 4] as u8;
-                                    self.fg_color = RGB8::new(r, g, b);
-                                    i += 4;
-                                }
-                            }
-                            _ => {}
+                23 => pen.italic = false,
+                24 => pen.underline = UnderlineStyle::None,
+                25 => pen.blink = false,
+                27 => pen.inverse = false,
+                28 => pen.hidden = false,
+                29 => pen.strikethrough = false,
+                30..=37 => pen.fg = Color::Indexed((v - 30) as u8),
+                39 => pen.fg = Color::Default,
+                40..=47 => pen.bg = Color::Indexed((v - 40) as u8),
+                49 => pen.bg = Color::Default,
+                53 => pen.overline = true,
+                55 => pen.overline = false,
+                59 => pen.underline_color = Color::Default,
+                90..=97 => pen.fg = Color::Indexed((v - 90 + 8) as u8),
+                100..=107 => pen.bg = Color::Indexed((v - 100 + 8) as u8),
+                38 | 48 | 58 => {
+                    // Extended color: 38;5;N / 38;2;R;G;B (48 background, 58 underline).
+                    // Arguments follow either as T.416 sub-parameters (38:2::R:G:B)
+                    // or as separate parameters in the legacy semicolon form.
+                    let parsed = if group.len() > 1 {
+                        parse_extended_color_subparams(&group[1..])
+                    } else {
+                        let args: Vec<u16> = groups[i + 1..].iter().map(|g| g[0]).collect();
+                        parse_extended_color(&args)
+                    };
+                    if let Some((color, consumed)) = parsed {
+                        match v {
+                            38 => pen.fg = color,
+                            48 => pen.bg = color,
+                            _ => pen.underline_color = color,
                         }
-                    }
-                }
-                48 => {
-                    // Extended background color
-                    if i + 1 < vals.len() {
-                        match vals[i + 1] {
-                            5 => {
-                                // 256-color: 48;5;N
-                                if i + 2 < vals.len() {
-                                    let idx = vals[i + 2];
-                                    self.bg_color = color_from_256(idx);
-                                    i += 2;
-                                }
-                            }
-                            2 => {
-                                // truecolor: 48;2;R;G;B
-                                if i + 4 < vals.len() {
-                                    let r = vals[i + 2] as u8;
-                                    let g = vals[i + 3] as u8;
-                                    let b = vals[i + 4] as u8;
-                                    self.bg_color = RGB8::new(r, g, b);
-                                    i += 4;
-                                }
-                            }
-                            _ => {}
+                        if group.len() == 1 {
+                            i += consumed;
                         }
                     }
                 }
@@ -259,27 +1032,35 @@ impl Emulator {
 
 impl Perform for Emulator {
     fn print(&mut self, c: char) {
-        self.write_char(c);
+        let charset = match self.single_shift.take() {
+            Some(g) => self.charsets[g],
+            None => self.charsets[self.gl],
+        };
+        let c = charset.map(c);
+        self.last_char = Some(c);
+
+        if !self.append_to_grapheme(c) {
+            self.write_char(c);
+        }
     }
 
     fn execute(&mut self, byte: u8) {
+        // Control characters other than BEL and the shifts cancel a pending wrap
+        if !matches!(byte, 0x07 | 0x0e | 0x0f) {
+            self.wrap_pending = false;
+        }
+
         match byte {
-            b'\n' => {
-                self.cursor.row += 1;
-                if self.cursor.row >= self.grid.height {
-                    self.cursor.row = self.grid.height - 1;
-                    // Scroll up
-                    self.grid.cells.remove(0);
-                    self.grid.cells.push(vec![Cell::default(); self.grid.width]);
-                }
+            b'\n' | b'\x0b' | b'\x0c' => {
+                // LF, VT and FF all move down one line
+                self.linefeed();
             }
             b'\r' => {
                 self.cursor.col = 0;
             }
             b'\t' => {
-                // Move to next tab stop (every 8 columns)
-                let next_tab = ((self.cursor.col / 8) + 1) * 8;
-                self.cursor.col = next_tab.min(self.grid.width - 1);
+                // Move to the next tab stop
+                self.tab_forward(1);
             }
             b'\x08' => {
                 // Backspace
@@ -287,6 +1068,14 @@ impl Perform for Emulator {
                     self.cursor.col -= 1;
                 }
             }
+            b'\x0e' => {
+                // SO - Shift Out, invoke G1 into GL
+                self.gl = 1;
+            }
+            b'\x0f' => {
+                // SI - Shift In, invoke G0 into GL
+                self.gl = 0;
+            }
             _ => {
                 log::debug!("Unhandled execute byte: {:02x}", byte);
             }
@@ -305,11 +1094,116 @@ impl Perform for Emulator {
         // Not needed for basic implementation
     }
 
-    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
-        // OSC sequences (like setting window title) - not critical for SVG generation
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        match params {
+            // OSC 8 ; params ; URI - start a hyperlink, or end it when the URI is empty.
+            // The URI itself may contain ';', which the parser splits on.
+            [b"8", _, uri @ ..] => {
+                let uri = uri.join(&b';');
+                let uri = String::from_utf8_lossy(&uri);
+                self.pen.hyperlink = (!uri.is_empty()).then(|| Arc::from(uri.as_ref()));
+            }
+            // OSC 0 / OSC 2 ; title - set the window title (OSC 1 only sets the icon name)
+            [b"0" | b"2", title @ ..] => {
+                let title = title.join(&b';');
+                let title = String::from_utf8_lossy(&title);
+                self.title = (!title.is_empty()).then(|| Arc::from(title.as_ref()));
+            }
+            // OSC 4 ; index ; spec [; index ; spec ...] - change palette entries
+            [b"4", pairs @ ..] => {
+                for pair in pairs.chunks_exact(2) {
+                    let index = std::str::from_utf8(pair[0])
+                        .ok()
+                        .and_then(|index| index.parse::<u8>().ok());
+                    if let (Some(index), Some(color)) = (index, parse_color_spec(pair[1])) {
+                        Arc::make_mut(&mut self.colors).palette.insert(index, color);
+                    }
+                }
+            }
+            // OSC 10 / OSC 11 ; spec - set the default foreground / background.
+            // Extra specs carry on to the following dynamic colors.
+            [code @ (b"10" | b"11"), specs @ ..] => {
+                for (i, spec) in specs.iter().enumerate() {
+                    let Some(color) = parse_color_spec(spec) else {
+                        continue;
+                    };
+                    let colors = Arc::make_mut(&mut self.colors);
+                    match (*code == b"11") as usize + i {
+                        0 => colors.fg = Some(color),
+                        1 => colors.bg = Some(color),
+                        _ => {}
+                    }
+                }
+            }
+            // OSC 104 [; index ...] - reset palette entries, all of them without arguments
+            [b"104", indices @ ..] => {
+                let colors = Arc::make_mut(&mut self.colors);
+                if indices.iter().all(|index| index.is_empty()) {
+                    colors.palette.clear();
+                }
+                for index in indices {
+                    if let Some(index) = std::str::from_utf8(index)
+                        .ok()
+                        .and_then(|index| index.parse::<u8>().ok())
+                    {
+                        colors.palette.remove(&index);
+                    }
+                }
+            }
+            [b"110", ..] => Arc::make_mut(&mut self.colors).fg = None,
+            [b"111", ..] => Arc::make_mut(&mut self.colors).bg = None,
+            _ => {
+                log::debug!("Unhandled OSC sequence: {:?}", params.first());
+            }
+        }
     }
 
-    fn csi_dispatch(&mut self, params: &Params, _intermediates: &[u8], _ignore: bool, c: char) {
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, c: char) {
+        match (c, intermediates) {
+            ('h' | 'l', [b'?']) => {
+                // DECSET / DECRST - DEC private modes
+                for p in params {
+                    self.set_private_mode(p[0], c == 'h');
+                }
+                return;
+            }
+            (_, []) => {}
+            ('p', [b'!']) => {
+                self.soft_reset();
+                return;
+            }
+            ('q', [b' ']) => {
+                // DECSCUSR - Set cursor style
+                let style = params
+                    .iter()
+                    .nth(0)
+                    .and_then(|p| p.first())
+                    .copied()
+                    .unwrap_or(0);
+                let (shape, blink) = match style {
+                    0 | 1 => (CursorShape::Block, true),
+                    2 => (CursorShape::Block, false),
+                    3 => (CursorShape::Underline, true),
+                    4 => (CursorShape::Underline, false),
+                    5 => (CursorShape::Bar, true),
+                    6 => (CursorShape::Bar, false),
+                    _ => return,
+                };
+                self.cursor.shape = shape;
+                self.cursor.blink = blink;
+                return;
+            }
+            _ => {
+                log::debug!("Unhandled CSI sequence: {:?} {}", intermediates, c);
+                return;
+            }
+        }
+
+        // Anything but SGR and REP moves or edits around the cursor and cancels a pending wrap
+        if !matches!(c, 'm' | 'b') {
+            self.wrap_pending = false;
+        }
+
         match c {
             'm' => {
                 // SGR - Select Graphic Rendition
@@ -317,22 +1211,69 @@ impl Perform for Emulator {
             }
             'H' | 'f' => {
                 // CUP - Cursor Position
-                let row = params
-                    .iter()
-                    .nth(0)
-                    .and_then(|p| p.first())
-                    .map(|&v| v.saturating_sub(1) as usize)
-                    .unwrap_or(0);
-                let col = params
-                    .iter()
-                    .nth(1)
-                    .and_then(|p| p.first())
-                    .map(|&v| v.saturating_sub(1) as usize)
-                    .unwrap_or(0);
-
+                let row = param(params, 0, 1) as usize - 1;
+                let col = param(params, 1, 1) as usize - 1;
+                self.goto(row, col);
+            }
+            'd' => {
+                // VPA - Line position absolute
+                self.goto(param(params, 0, 1) as usize - 1, self.cursor.col);
+            }
+            'e' => {
+                // VPR - Line position relative
+                let row = self.cursor.row + param(params, 0, 1) as usize;
                 self.cursor.row = row.min(self.grid.height - 1);
+            }
+            'G' | '`' => {
+                // CHA / HPA - Character position absolute
+                self.cursor.col = (param(params, 0, 1) as usize - 1).min(self.grid.width - 1);
+            }
+            'a' => {
+                // HPR - Character position relative
+                let col = self.cursor.col + param(params, 0, 1) as usize;
                 self.cursor.col = col.min(self.grid.width - 1);
             }
+            'E' => {
+                // CNL - Cursor next line
+                self.cursor_down(param(params, 0, 1) as usize);
+                self.cursor.col = 0;
+            }
+            'F' => {
+                // CPL - Cursor previous line
+                self.cursor_up(param(params, 0, 1) as usize);
+                self.cursor.col = 0;
+            }
+            'b' => {
+                // REP - Repeat the preceding graphic character
+                if let Some(c) = self.last_char {
+                    let n = (param(params, 0, 1) as usize).min(self.grid.width * self.grid.height);
+                    for _ in 0..n {
+                        self.write_char(c);
+                    }
+                }
+            }
+            'I' => {
+                // CHT - Cursor forward tabulation
+                self.tab_forward(param(params, 0, 1) as usize);
+            }
+            'Z' => {
+                // CBT - Cursor backward tabulation
+                self.tab_backward(param(params, 0, 1) as usize);
+            }
+            'g' => {
+                // TBC - Tab clear: 0 at the cursor column, 3 everywhere
+                match param(params, 0, 0) {
+                    0 => self.tabs[self.cursor.col] = false,
+                    3 => self.tabs.fill(false),
+                    _ => {}
+                }
+            }
+            'h' | 'l' => {
+                // SM / RM - Set / reset mode
+                for p in params {
+                    self.set_mode(p[0], c == 'h');
+                }
+            }
             'J' => {
                 // ED - Erase Display
                 let mode = params
@@ -341,6 +1282,10 @@ impl Perform for Emulator {
                     .and_then(|p| p.first())
                     .copied()
                     .unwrap_or(0);
+                let blank = self.blank_cell();
+                if mode < 2 {
+                    self.clear_wide_char(self.cursor.row, self.cursor.col);
+                }
 
                 match mode {
                     0 => {
@@ -353,7 +1298,7 @@ impl Perform for Emulator {
                             };
                             for col in start_col..self.grid.width {
                                 if let Some(cell) = self.grid.get_cell_mut(row, col) {
-                                    *cell = Cell::default();
+                                    *cell = blank.clone();
                                 }
                             }
                         }
@@ -368,14 +1313,24 @@ impl Perform for Emulator {
                             };
                             for col in 0..=end_col {
                                 if let Some(cell) = self.grid.get_cell_mut(row, col) {
-                                    *cell = Cell::default();
+                                    *cell = blank.clone();
                                 }
                             }
                         }
                     }
                     2 => {
                         // Clear entire screen
-                        self.grid.clear();
+                        for row in 0..self.grid.height {
+                            for col in 0..self.grid.width {
+                                if let Some(cell) = self.grid.get_cell_mut(row, col) {
+                                    *cell = blank.clone();
+                                }
+                            }
+                        }
+                    }
+                    3 => {
+                        // Clear the scrollback
+                        self.scrollback.clear();
                     }
                     _ => {}
                 }
@@ -388,13 +1343,17 @@ impl Perform for Emulator {
                     .and_then(|p| p.first())
                     .copied()
                     .unwrap_or(0);
+                let blank = self.blank_cell();
+                if mode < 2 {
+                    self.clear_wide_char(self.cursor.row, self.cursor.col);
+                }
 
                 match mode {
                     0 => {
                         // Clear from cursor to end of line
                         for col in self.cursor.col..self.grid.width {
                             if let Some(cell) = self.grid.get_cell_mut(self.cursor.row, col) {
-                                *cell = Cell::default();
+                                *cell = blank.clone();
                             }
                         }
                     }
@@ -402,7 +1361,7 @@ impl Perform for Emulator {
                         // Clear from beginning to cursor
                         for col in 0..=self.cursor.col {
                             if let Some(cell) = self.grid.get_cell_mut(self.cursor.row, col) {
-                                *cell = Cell::default();
+                                *cell = blank.clone();
                             }
                         }
                     }
@@ -410,7 +1369,7 @@ impl Perform for Emulator {
                         // Clear entire line
                         for col in 0..self.grid.width {
                             if let Some(cell) = self.grid.get_cell_mut(self.cursor.row, col) {
-                                *cell = Cell::default();
+                                *cell = blank.clone();
                             }
                         }
                     }
@@ -419,57 +1378,92 @@ impl Perform for Emulator {
             }
             'A' => {
                 // CUU - Cursor Up
-                let n = params
-                    .iter()
-                    .nth(0)
-                    .and_then(|p| p.first())
-                    .copied()
-                    .unwrap_or(1) as usize;
-                self.cursor.row = self.cursor.row.saturating_sub(n);
+                self.cursor_up(param(params, 0, 1) as usize);
             }
             'B' => {
                 // CUD - Cursor Down
-                let n = params
-                    .iter()
-                    .nth(0)
-                    .and_then(|p| p.first())
-                    .copied()
-                    .unwrap_or(1) as usize;
-                self.cursor.row = (self.cursor.row + n).min(self.grid.height - 1);
+                self.cursor_down(param(params, 0, 1) as usize);
             }
             'C' => {
                 // CUF - Cursor Forward
-                let n = params
-                    .iter()
-                    .nth(0)
-                    .and_then(|p| p.first())
-                    .copied()
-                    .unwrap_or(1) as usize;
-                self.cursor.col = (self.cursor.col + n).min(self.grid.width - 1);
+                let col = self.cursor.col + param(params, 0, 1) as usize;
+                self.cursor.col = col.min(self.grid.width - 1);
             }
             'D' => {
                 // CUB - Cursor Back
-                let n = params
-                    .iter()
-                    .nth(0)
-                    .and_then(|p| p.first())
-                    .copied()
-                    .unwrap_or(1) as usize;
-                self.cursor.col = self.cursor.col.saturating_sub(n);
+                self.cursor.col = self.cursor.col.saturating_sub(param(params, 0, 1) as usize);
+            }
+            'r' => {
+                // DECSTBM - Set top and bottom margins
+                let top = param(params, 0, 1) as usize - 1;
+                let bottom =
+                    (param(params, 1, self.grid.height as u16) as usize).min(self.grid.height) - 1;
+                if top < bottom {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.goto(0, 0);
+                }
+            }
+            '@' => {
+                // ICH - Insert blank characters, shifting the rest of the line right
+                self.insert_blanks(param(params, 0, 1) as usize);
+            }
+            'P' => {
+                // DCH - Delete characters, shifting the rest of the line left
+                let blank = self.blank_cell();
+                let (row, col) = (self.cursor.row, self.cursor.col);
+                let n = (param(params, 0, 1) as usize).min(self.grid.width - col);
+                // Don't split wide characters at either edge of the deleted range
+                self.clear_wide_char(row, col);
+                self.clear_wide_char(row, col + n - 1);
+                if let Some(line) = self.grid.cells.get_mut(row) {
+                    let tail = &mut line[col..];
+                    tail.rotate_left(n);
+                    let len = tail.len();
+                    tail[len - n..].fill(blank);
+                }
+            }
+            'X' => {
+                // ECH - Erase characters without moving the cursor
+                let blank = self.blank_cell();
+                let (row, col) = (self.cursor.row, self.cursor.col);
+                let end = (col + param(params, 0, 1) as usize).min(self.grid.width);
+                // Don't leave half of a wide character at either edge
+                self.clear_wide_char(row, col);
+                self.clear_wide_char(row, end - 1);
+                if let Some(line) = self.grid.cells.get_mut(row) {
+                    line[col..end].fill(blank);
+                }
+            }
+            'L' => {
+                // IL - Insert lines at the cursor, within the scroll region
+                if (self.scroll_top..=self.scroll_bottom).contains(&self.cursor.row) {
+                    self.scroll_lines_down(self.cursor.row, param(params, 0, 1) as usize);
+                    self.cursor.col = 0;
+                }
+            }
+            'M' => {
+                // DL - Delete lines at the cursor, within the scroll region
+                if (self.scroll_top..=self.scroll_bottom).contains(&self.cursor.row) {
+                    self.scroll_lines_up(self.cursor.row, param(params, 0, 1) as usize);
+                    self.cursor.col = 0;
+                }
+            }
+            'S' => {
+                // SU - Scroll up
+                self.scroll_up(param(params, 0, 1) as usize);
+            }
+            'T' => {
+                // SD - Scroll down
+                self.scroll_down(param(params, 0, 1) as usize);
             }
             's' => {
-                // Save cursor position
-                self.saved_cursor = Some(Cursor {
-                    row: self.cursor.row,
-                    col: self.cursor.col,
-                });
+                // SCOSC - Save cursor, same as DECSC
+                self.save_cursor();
             }
             'u' => {
-                // Restore cursor position
-                if let Some(saved) = &self.saved_cursor {
-                    self.cursor.row = saved.row;
-                    self.cursor.col = saved.col;
-                }
+                // SCORC - Restore cursor, same as DECRC
+                self.restore_cursor();
             }
             _ => {
                 log::debug!("Unhandled CSI sequence: {}", c);
@@ -477,67 +1471,178 @@ impl Perform for Emulator {
         }
     }
 
-    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
-        // ESC sequences - not critical for basic implementation
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        if matches!((intermediates, byte), ([], b'D' | b'E' | b'M')) {
+            self.wrap_pending = false;
+        }
+
+        match (intermediates, byte) {
+            ([], b'D') => {
+                // IND - Index
+                self.linefeed();
+            }
+            ([], b'E') => {
+                // NEL - Next line
+                self.cursor.col = 0;
+                self.linefeed();
+            }
+            ([], b'M') => {
+                // RI - Reverse index
+                self.reverse_index();
+            }
+            ([], b'7') => {
+                // DECSC - Save cursor
+                self.save_cursor();
+            }
+            ([], b'8') => {
+                // DECRC - Restore cursor
+                self.restore_cursor();
+            }
+            ([], b'c') => {
+                // RIS - Reset to initial state
+                self.full_reset();
+            }
+            ([], b'H') => {
+                // HTS - Set a tab stop at the cursor column
+                self.tabs[self.cursor.col] = true;
+            }
+            ([], b'N') => {
+                // SS2 - Single shift 2
+                self.single_shift = Some(2);
+            }
+            ([], b'O') => {
+                // SS3 - Single shift 3
+                self.single_shift = Some(3);
+            }
+            ([], b'n') => {
+                // LS2 - Locking shift 2
+                self.gl = 2;
+            }
+            ([], b'o') => {
+                // LS3 - Locking shift 3
+                self.gl = 3;
+            }
+            ([designator @ (b'(' | b')' | b'*' | b'+')], _) => {
+                // SCS - Designate a character set into G0-G3
+                let g = (designator - b'(') as usize;
+                self.charsets[g] = match byte {
+                    b'0' => Charset::DecSpecialGraphics,
+                    b'A' => Charset::Uk,
+                    b'B' => Charset::Ascii,
+                    _ => {
+                        log::debug!("Unsupported character set: {}", byte as char);
+                        Charset::Ascii
+                    }
+                };
+            }
+            _ => {
+                log::debug!(
+                    "Unhandled ESC sequence: {:?} {}",
+                    intermediates,
+                    byte as char
+                );
+            }
+        }
     }
 }
 
-fn ansi_color_to_rgb(color: u16, bright: bool) -> RGB8 {
-    let colors = if bright {
-        // Bright colors
-        [
-            RGB8::new(127, 127, 127), // Black (bright = gray)
-            RGB8::new(255, 0, 0),     // Red
-            RGB8::new(0, 255, 0),     // Green
-            RGB8::new(255, 255, 0),   // Yellow
-            RGB8::new(92, 92, 255),   // Blue
-            RGB8::new(255, 0, 255),   // Magenta
-            RGB8::new(0, 255, 255),   // Cyan
-            RGB8::new(255, 255, 255), // White
-        ]
-    } else {
-        // Normal colors
-        [
-            RGB8::new(0, 0, 0),       // Black
-            RGB8::new(205, 0, 0),     // Red
-            RGB8::new(0, 205, 0),     // Green
-            RGB8::new(205, 205, 0),   // Yellow
-            RGB8::new(0, 0, 238),     // Blue
-            RGB8::new(205, 0, 205),   // Magenta
-            RGB8::new(0, 205, 205),   // Cyan
-            RGB8::new(229, 229, 229), // White
-        ]
-    };
+/// Whether a line has neither text nor a background of its own.
+fn is_blank_line(line: &[Cell]) -> bool {
+    line.iter()
+        .all(|cell| cell.ch == ' ' && cell.bg == Color::Default && !cell.inverse)
+}
 
-    colors[color as usize % 8]
+/// Drops the blank lines at the end of `lines`.
+fn trim_blank_lines(lines: &[Vec<Cell>]) -> &[Vec<Cell>] {
+    let keep = lines
+        .iter()
+        .rposition(|line| !is_blank_line(line))
+        .map_or(0, |last| last + 1);
+    &lines[..keep]
 }
 
-fn color_from_256(index: u16) -> RGB8 {
-    match index {
-        0..=15 => {
-            let bright = index >= 8;
-            let base = if bright { index - 8 } else { index };
-            ansi_color_to_rgb(base, bright)
-        }
-        16..=231 => {
-            // 6x6x6 color cube
-            let c = index - 16;
-            let r = c / 36;
-            let g = (c % 36) / 6;
-            let b = c % 6;
-            let level = |n: u16| -> u8 {
-                const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
-                LEVELS[n as usize]
-            };
-            RGB8::new(level(r), level(g), level(b))
+/// Tab stops start out every 8 columns.
+fn is_default_tab_stop(col: usize) -> bool {
+    col % 8 == 0
+}
+
+/// Emoji skin tone modifiers (U+1F3FB..U+1F3FF) attach to the preceding emoji.
+fn is_emoji_modifier(ch: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&ch)
+}
+
+/// Regional indicators pair up into flag emoji.
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+/// Parses the arguments following an extended color SGR (38/48), returning
+/// the color and the number of parameters consumed.
+fn parse_extended_color(args: &[u16]) -> Option<(Color, usize)> {
+    match args {
+        [5, idx, ..] => Some((Color::Indexed(*idx as u8), 2)),
+        [2, r, g, b, ..] => Some((Color::Rgb(RGB8::new(*r as u8, *g as u8, *b as u8)), 4)),
+        _ => None,
+    }
+}
+
+/// Parses the colon-separated form of an extended color. Unlike the legacy
+/// form, direct colors may carry a colour-space slot before the components
+/// (`2:CS:R:G:B`), which is usually left empty and ignored.
+fn parse_extended_color_subparams(args: &[u16]) -> Option<(Color, usize)> {
+    match args {
+        [2, _, r, g, b, ..] => Some((Color::Rgb(RGB8::new(*r as u8, *g as u8, *b as u8)), 5)),
+        _ => parse_extended_color(args),
+    }
+}
+
+/// Parses an X11 color specification as used by OSC 4/10/11: `rgb:R/G/B`
+/// with 1-4 hex digits per component, or `#RGB` with 1-4 digits per
+/// component. Queries (`?`) and color names yield `None`.
+fn parse_color_spec(spec: &[u8]) -> Option<RGB8> {
+    let spec = std::str::from_utf8(spec).ok()?;
+
+    // Scales a component of `digits` hex digits to 8 bits
+    let scale = |component: &str| -> Option<u8> {
+        if component.is_empty() || component.len() > 4 {
+            return None;
         }
-        232..=255 => {
-            // grayscale ramp
-            let v = 8 + 10 * (index - 232);
-            let v = v as u8;
-            RGB8::new(v, v, v)
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1u32 << (4 * component.len())) - 1;
+        Some((value * 255 / max) as u8)
+    };
+
+    if let Some(rgb) = spec.strip_prefix("rgb:") {
+        let mut parts = rgb.split('/');
+        let (r, g, b) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
         }
-        _ => RGB8::new(204, 204, 204),
+        return Some(RGB8::new(scale(r)?, scale(g)?, scale(b)?));
+    }
+
+    // Unlike rgb:, the legacy #-form keeps the most significant bits
+    let hex = spec.strip_prefix('#')?;
+    if hex.is_empty() || hex.len() % 3 != 0 || hex.len() > 12 || !hex.is_ascii() {
+        return None;
+    }
+    let n = hex.len() / 3;
+    let component = |i: usize| -> Option<u8> {
+        let value = u32::from_str_radix(&hex[i * n..(i + 1) * n], 16).ok()?;
+        Some(match n {
+            1 => value * 0x11,
+            _ => value >> (4 * (n - 2)),
+        } as u8)
+    };
+    Some(RGB8::new(component(0)?, component(1)?, component(2)?))
+}
+
+/// Returns the numeric parameter at `index`, treating a missing or zero
+/// value as `default` like most CSI sequences do.
+fn param(params: &Params, index: usize, default: u16) -> u16 {
+    match params.iter().nth(index).and_then(|p| p.first()) {
+        Some(&v) if v != 0 => v,
+        _ => default,
     }
 }
 
@@ -547,23 +1652,570 @@ pub struct StateSnapshot {
     pub grid: Grid,
 }
 
-pub fn process_events(
-    events: &[crate::asciicast::Event],
-    width: usize,
-    height: usize,
-) -> Vec<StateSnapshot> {
+pub fn process_events(events: &[Event], width: usize, height: usize) -> Vec<StateSnapshot> {
     let mut emulator = Emulator::new(width, height);
     let mut snapshots = Vec::new();
 
     for event in events {
-        if event.event_type == crate::asciicast::EventType::Output {
-            emulator.process_string(&event.data);
+        if matches!(event.event_type, EventType::Output | EventType::Resize) {
+            emulator.feed_event(event);
             snapshots.push(StateSnapshot {
                 time: event.time,
-                grid: emulator.grid.clone(),
+                grid: emulator.get_frame(),
             });
         }
     }
 
     snapshots
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sgr_colors_are_stored_as_references() {
+        let mut emulator = Emulator::new(10, 2);
+        emulator.process_string("\x1b[31ma\x1b[38;5;208mb\x1b[48;2;1;2;3mc\x1b[0md");
+
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 0).unwrap().fg, Color::Indexed(1));
+        assert_eq!(frame.get_cell(0, 1).unwrap().fg, Color::Indexed(208));
+        assert_eq!(
+            frame.get_cell(0, 2).unwrap().bg,
+            Color::Rgb(RGB8::new(1, 2, 3))
+        );
+        assert_eq!(frame.get_cell(0, 3).unwrap().fg, Color::Default);
+        assert_eq!(frame.get_cell(0, 3).unwrap().bg, Color::Default);
+    }
+
+    #[test]
+    fn test_sgr_colon_subparameters() {
+        let mut emulator = Emulator::new(10, 1);
+        emulator.process_string(
+            "\x1b[38:2::255:0:0ma\x1b[38:2:1:2:3;1mb\x1b[48:5:208;58:2::9:8:7mc\x1b[38;2;4;5;6;3md",
+        );
+
+        let frame = emulator.get_frame();
+        let a = frame.get_cell(0, 0).unwrap();
+        assert_eq!(a.fg, Color::Rgb(RGB8::new(255, 0, 0)));
+        assert!(!a.bold && !a.italic);
+
+        let b = frame.get_cell(0, 1).unwrap();
+        assert_eq!(b.fg, Color::Rgb(RGB8::new(1, 2, 3)));
+        assert!(b.bold);
+
+        let c = frame.get_cell(0, 2).unwrap();
+        assert_eq!(c.bg, Color::Indexed(208));
+        assert_eq!(c.underline_color, Color::Rgb(RGB8::new(9, 8, 7)));
+
+        let d = frame.get_cell(0, 3).unwrap();
+        assert_eq!(d.fg, Color::Rgb(RGB8::new(4, 5, 6)));
+        assert!(d.italic);
+    }
+
+    #[test]
+    fn test_sgr_attributes() {
+        let mut emulator = Emulator::new(10, 1);
+        emulator.process_string(
+            "\x1b[2;5;7;8;9;53ma\x1b[4:3;58;5;1mb\x1b[21mc\x1b[22;24;25;27;28;29;55;59md",
+        );
+
+        let frame = emulator.get_frame();
+        let a = frame.get_cell(0, 0).unwrap();
+        assert!(a.dim && a.blink && a.inverse && a.hidden && a.strikethrough && a.overline);
+        assert_eq!(a.underline, UnderlineStyle::None);
+
+        let b = frame.get_cell(0, 1).unwrap();
+        assert_eq!(b.underline, UnderlineStyle::Curly);
+        assert!(!b.italic);
+        assert_eq!(b.underline_color, Color::Indexed(1));
+
+        assert_eq!(
+            frame.get_cell(0, 2).unwrap().underline,
+            UnderlineStyle::Double
+        );
+
+        let d = frame.get_cell(0, 3).unwrap();
+        assert_eq!(
+            Cell {
+                ch: 'd',
+                ..Cell::default()
+            },
+            *d
+        );
+    }
+
+    #[test]
+    fn test_cursor_state_in_frame() {
+        let mut emulator = Emulator::new(10, 3);
+        emulator.process_string("ab\r\ncd");
+
+        let frame = emulator.get_frame();
+        assert_eq!((frame.cursor.row, frame.cursor.col), (1, 2));
+        assert!(frame.cursor.visible);
+
+        emulator.process_string("\x1b[?25l\x1b[6 q");
+        let frame = emulator.get_frame();
+        assert!(!frame.cursor.visible);
+        assert_eq!(frame.cursor.shape, CursorShape::Bar);
+        assert!(!frame.cursor.blink);
+    }
+
+    #[test]
+    fn test_resize_keeps_cursor_line() {
+        let mut emulator = Emulator::new(10, 4);
+        emulator.process_string("1\r\n2\r\n3\r\n4");
+
+        emulator.resize(5, 2);
+        let frame = emulator.get_frame();
+        assert_eq!((frame.width, frame.height), (5, 2));
+        assert_eq!(frame.get_cell(0, 0).unwrap().ch, '3');
+        assert_eq!(frame.get_cell(1, 0).unwrap().ch, '4');
+        assert_eq!((frame.cursor.row, frame.cursor.col), (1, 1));
+
+        emulator.resize(12, 3);
+        let frame = emulator.get_frame();
+        assert_eq!((frame.width, frame.height), (12, 3));
+        assert_eq!(frame.get_cell(2, 11).unwrap().ch, ' ');
+    }
+
+    #[test]
+    fn test_alternate_screen_restores_primary() {
+        let mut emulator = Emulator::new(10, 3);
+        emulator.process_string("$ vim");
+        emulator.process_string("\x1b[?1049h\x1b[Hedit");
+        assert!(emulator.is_alternate_screen());
+        assert_eq!(emulator.get_frame().get_cell(0, 0).unwrap().ch, 'e');
+
+        emulator.process_string("\x1b[?1049l");
+        assert!(!emulator.is_alternate_screen());
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 2).unwrap().ch, 'v');
+        assert_eq!((frame.cursor.row, frame.cursor.col), (0, 5));
+
+        // The alternate screen starts out cleared on the next switch
+        emulator.process_string("\x1b[?1049h");
+        assert_eq!(emulator.get_frame().get_cell(0, 0).unwrap().ch, ' ');
+    }
+
+    #[test]
+    fn test_zero_dimensions() {
+        let mut emulator = Emulator::new(0, 0);
+        emulator.process_string("ab\r\ncd\x1b[5G");
+        let frame = emulator.get_frame();
+        assert_eq!((frame.width, frame.height), (1, 1));
+    }
+
+    #[test]
+    fn test_resize_on_alternate_screen_keeps_primary_cursor_line() {
+        let mut emulator = Emulator::new(4, 4).with_scrollback(10);
+        emulator.process_string("1\r\n2\r\n3\r\n4\x1b[?1049h");
+        emulator.resize(4, 2);
+        emulator.process_string("\x1b[?1049l");
+
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "3");
+        assert_eq!(row_text(&frame, 1), "4");
+        assert_eq!((frame.cursor.row, frame.cursor.col), (1, 1));
+        assert_eq!(emulator.scrollback().len(), 2);
+        assert_eq!(emulator.scrollback()[0][0].ch, '1');
+    }
+
+    fn row_text(frame: &Frame, row: usize) -> String {
+        (0..frame.width)
+            .map(|col| frame.get_cell(row, col).unwrap().ch)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn test_scroll_region_keeps_header_and_footer() {
+        let mut emulator = Emulator::new(10, 5);
+        emulator.process_string("head\x1b[5;1Hfoot\x1b[2;4r\x1b[2;1Ha\r\nb\r\nc\r\nd");
+
+        let frame = emulator.get_frame();
+        let rows: Vec<String> = (0..5).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["head", "b", "c", "d", "foot"]);
+
+        // Reverse index at the top margin scrolls the region down
+        emulator.process_string("\x1b[2;1H\x1bMz");
+        let frame = emulator.get_frame();
+        let rows: Vec<String> = (0..5).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["head", "z", "b", "c", "foot"]);
+
+        // SU / SD scroll only within the margins
+        emulator.process_string("\x1b[2S");
+        let frame = emulator.get_frame();
+        let rows: Vec<String> = (0..5).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["head", "c", "", "", "foot"]);
+    }
+
+    #[test]
+    fn test_wide_characters_take_two_cells() {
+        let mut emulator = Emulator::new(5, 2);
+        emulator.process_string("a中b");
+
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 1).unwrap().ch, '中');
+        assert_eq!(frame.get_cell(0, 1).unwrap().width, 2);
+        assert_eq!(frame.get_cell(0, 2).unwrap().width, 0);
+        assert_eq!(frame.get_cell(0, 3).unwrap().ch, 'b');
+        assert_eq!(frame.cursor.col, 4);
+
+        // Not enough room at the right edge: wrap before writing
+        emulator.process_string("文");
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 4).unwrap().ch, ' ');
+        assert_eq!(frame.get_cell(1, 0).unwrap().ch, '文');
+        assert_eq!(frame.cursor.col, 2);
+
+        // Overwriting half of a wide character blanks the other half
+        emulator.process_string("\x1b[1;3Hx");
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 1).unwrap().ch, ' ');
+        assert_eq!(frame.get_cell(0, 1).unwrap().width, 1);
+        assert_eq!(frame.get_cell(0, 2).unwrap().ch, 'x');
+    }
+
+    #[test]
+    fn test_grapheme_clusters_share_a_cell() {
+        let mut emulator = Emulator::new(10, 1);
+        // "e" + combining acute, thumbs up + skin tone, family ZWJ sequence, flag
+        emulator.process_string("e\u{301}x👍\u{1F3FD}👨\u{200D}👩\u{200D}👧🇨🇳|");
+
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 0).unwrap().grapheme(), "e\u{301}");
+        assert_eq!(frame.get_cell(0, 1).unwrap().ch, 'x');
+        assert_eq!(frame.get_cell(0, 2).unwrap().grapheme(), "👍\u{1F3FD}");
+        assert_eq!(
+            frame.get_cell(0, 4).unwrap().grapheme(),
+            "👨\u{200D}👩\u{200D}👧"
+        );
+        assert_eq!(frame.get_cell(0, 6).unwrap().grapheme(), "🇨🇳");
+        assert_eq!(frame.cursor.col, 8);
+    }
+
+    #[test]
+    fn test_deferred_autowrap() {
+        let mut emulator = Emulator::new(5, 3);
+        // Filling the line and returning must not move to the next row
+        emulator.process_string("12345\r");
+        let frame = emulator.get_frame();
+        assert_eq!((frame.cursor.row, frame.cursor.col), (0, 0));
+
+        // The wrap happens once the next printable character arrives
+        emulator.process_string("abcde\x1b[1mf");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "abcde");
+        assert_eq!(row_text(&frame, 1), "f");
+
+        // With DECAWM off the last column is overwritten instead
+        emulator.process_string("\x1b[?7l\x1b[3;1Hvwxyz!");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 2), "vwxy!");
+        assert_eq!((frame.cursor.row, frame.cursor.col), (2, 4));
+    }
+
+    #[test]
+    fn test_line_editing_sequences() {
+        let mut emulator = Emulator::new(10, 3);
+        emulator.process_string("abcdef\x1b[1;3H\x1b[2@XY");
+        assert_eq!(row_text(&emulator.get_frame(), 0), "abXYcdef");
+
+        emulator.process_string("\x1b[1;3H\x1b[3P");
+        assert_eq!(row_text(&emulator.get_frame(), 0), "abdef");
+
+        emulator.process_string("\x1b[41m\x1b[2X\x1b[0m");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "ab  f");
+        assert_eq!(frame.get_cell(0, 2).unwrap().bg, Color::Indexed(1));
+        assert_eq!(frame.get_cell(0, 4).unwrap().bg, Color::Default);
+
+        emulator.process_string("\x1b[2;1Hline2\x1b[3;1Hline3\x1b[2;3H\x1b[L");
+        let frame = emulator.get_frame();
+        let rows: Vec<String> = (0..3).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["ab  f", "", "line2"]);
+        assert_eq!(frame.cursor.col, 0);
+
+        emulator.process_string("\x1b[1;1H\x1b[M");
+        let frame = emulator.get_frame();
+        let rows: Vec<String> = (0..3).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["", "line2", ""]);
+    }
+
+    #[test]
+    fn test_osc8_hyperlinks() {
+        let mut emulator = Emulator::new(12, 1);
+        emulator.process_string(
+            "a\x1b]8;id=1;https://example.com/?q=a;b\x1b\\li\x1b[0mnk\x1b]8;;\x07z",
+        );
+
+        let frame = emulator.get_frame();
+        let link = |col| frame.get_cell(0, col).unwrap().hyperlink.as_deref();
+        assert_eq!(link(0), None);
+        assert_eq!(link(1), Some("https://example.com/?q=a;b"));
+        assert_eq!(link(4), Some("https://example.com/?q=a;b"));
+        assert_eq!(link(5), None);
+    }
+
+    #[test]
+    fn test_osc_title() {
+        let mut emulator = Emulator::new(10, 1);
+        assert_eq!(emulator.get_frame().title, None);
+
+        emulator.process_string("\x1b]0;vim; main.rs\x07");
+        assert_eq!(emulator.get_frame().title.as_deref(), Some("vim; main.rs"));
+
+        emulator.process_string("\x1b]1;icon\x07\x1b]2;~/src\x1b\\");
+        assert_eq!(emulator.get_frame().title.as_deref(), Some("~/src"));
+    }
+
+    #[test]
+    fn test_parse_color_spec() {
+        assert_eq!(
+            parse_color_spec(b"rgb:ff/80/00"),
+            Some(RGB8::new(255, 128, 0))
+        );
+        assert_eq!(
+            parse_color_spec(b"rgb:ffff/0/8080"),
+            Some(RGB8::new(255, 0, 128))
+        );
+        assert_eq!(
+            parse_color_spec(b"#1a2b3c"),
+            Some(RGB8::new(0x1a, 0x2b, 0x3c))
+        );
+        assert_eq!(parse_color_spec(b"#fa0"), Some(RGB8::new(255, 170, 0)));
+        assert_eq!(
+            parse_color_spec(b"#123456789abc"),
+            Some(RGB8::new(0x12, 0x56, 0x9a))
+        );
+        assert_eq!(parse_color_spec(b"?"), None);
+        assert_eq!(parse_color_spec(b"red"), None);
+    }
+
+    #[test]
+    fn test_osc_color_changes() {
+        let mut emulator = Emulator::new(10, 1);
+        emulator.process_string(
+            "\x1b]4;1;rgb:11/22/33;200;#445566\x07\x1b]10;#ffffff;#000000\x07\x1b]4;2;?\x07",
+        );
+
+        let colors = emulator.get_frame().colors;
+        assert_eq!(colors.palette.get(&1), Some(&RGB8::new(0x11, 0x22, 0x33)));
+        assert_eq!(colors.palette.get(&200), Some(&RGB8::new(0x44, 0x55, 0x66)));
+        assert_eq!(colors.palette.get(&2), None);
+        assert_eq!(colors.fg, Some(RGB8::new(255, 255, 255)));
+        assert_eq!(colors.bg, Some(RGB8::new(0, 0, 0)));
+
+        emulator.process_string("\x1b]104;1\x07\x1b]110\x07");
+        let colors = emulator.get_frame().colors;
+        assert_eq!(colors.palette.len(), 1);
+        assert_eq!(colors.fg, None);
+        assert_eq!(colors.bg, Some(RGB8::new(0, 0, 0)));
+
+        emulator.process_string("\x1b]104\x07\x1b]111\x07");
+        assert_eq!(*emulator.get_frame().colors, ColorOverrides::default());
+    }
+
+    #[test]
+    fn test_charsets() {
+        let mut emulator = Emulator::new(10, 3);
+        emulator.process_string("\x1b(0lqk\x1b(B x\r\n");
+        emulator.process_string("\x1b)0a\x0eq\x0fq\r\n");
+        emulator.process_string("\x1b*A\x1bN##\x1b+0\x1bOx");
+
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "┌─┐ x");
+        assert_eq!(row_text(&frame, 1), "a─q");
+        assert_eq!(row_text(&frame, 2), "£#│");
+    }
+
+    #[test]
+    fn test_cursor_addressing() {
+        let mut emulator = Emulator::new(10, 5);
+        emulator.process_string("\x1b[3G\x1b[2da\x1b[2aXb\x1b[3`c\x1b[ed");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 1), "  c  Xb");
+        assert_eq!(row_text(&frame, 2), "   d");
+
+        emulator.process_string("\x1b[Ee\x1b[2Ff\x1b[4b");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 3), "e");
+        assert_eq!(row_text(&frame, 1), "fffffXb");
+        assert_eq!((frame.cursor.row, frame.cursor.col), (1, 5));
+    }
+
+    #[test]
+    fn test_save_restore_and_modes() {
+        let mut emulator = Emulator::new(10, 5);
+        emulator.process_string("\x1b[2;3H\x1b[1;31m\x1b(0\x1b7\x1b[0m\x1b(B\x1b[H\x1b8q\x1b(B");
+        let frame = emulator.get_frame();
+        let cell = frame.get_cell(1, 2).unwrap();
+        assert_eq!(cell.ch, '─');
+        assert!(cell.bold);
+        assert_eq!(cell.fg, Color::Indexed(1));
+
+        // Origin mode addresses rows relative to the scroll region
+        emulator.process_string("\x1b[2;4r\x1b[?6h\x1b[10;1Hx\x1b[?6l");
+        assert_eq!(row_text(&emulator.get_frame(), 3), "x");
+
+        // Insert mode shifts the rest of the line
+        emulator.process_string("\x1b[5;1Habc\x1b[5;2H\x1b[4hXY\x1b[4lZ");
+        assert_eq!(row_text(&emulator.get_frame(), 4), "aXYZc");
+
+        // DECSTR resets the pen and modes but keeps the screen
+        emulator.process_string("\x1b[7m\x1b[?25l\x1b[!p");
+        assert!(!emulator.pen.inverse);
+        assert!(emulator.get_frame().cursor.visible);
+        assert_eq!(row_text(&emulator.get_frame(), 4), "aXYZc");
+
+        // RIS clears everything
+        emulator.process_string("\x1bc");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 4), "");
+        assert_eq!((frame.cursor.row, frame.cursor.col), (0, 0));
+    }
+
+    #[test]
+    fn test_tab_stops() {
+        let mut emulator = Emulator::new(20, 2);
+        emulator.process_string("a\tb\x1b[3g\r\t\x1b[4GH\x1bH\x1b[12GI\x1bH\r\tc\x1b[Id");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "a  Hc   b  Id");
+
+        emulator.process_string("\x1b[2Z\x1b[g\x1b[Ze");
+        assert_eq!(row_text(&emulator.get_frame(), 0), "e  Hc   b  Id");
+
+        // Columns added by a resize get the default stops
+        emulator.resize(30, 2);
+        emulator.process_string("\x1b[2;14H\tf");
+        assert_eq!(emulator.get_frame().get_cell(1, 24).unwrap().ch, 'f');
+    }
+
+    #[test]
+    fn test_scrollback() {
+        let mut emulator = Emulator::new(5, 3).with_scrollback(2);
+        emulator.process_string("1\r\n2\r\n3\r\n4\r\n5\r\n6");
+        let lines: Vec<char> = emulator.scrollback().iter().map(|l| l[0].ch).collect();
+        assert_eq!(lines, vec!['2', '3']);
+
+        // Scrolling inside a region below the top or on the alternate screen
+        // doesn't add to the scrollback
+        emulator.process_string("\x1b[?1049h\r\n\r\n\r\n\x1b[?1049l\x1b[2;3r\x1b[3;1H\r\n");
+        assert_eq!(emulator.scrollback().len(), 2);
+        assert_eq!(emulator.scrollback()[1][0].ch, '3');
+
+        emulator.process_string("\x1b[3J");
+        assert!(emulator.scrollback().is_empty());
+
+        // Disabled by default
+        let mut emulator = Emulator::new(5, 2);
+        emulator.process_string("1\r\n2\r\n3");
+        assert!(emulator.scrollback().is_empty());
+    }
+
+    #[test]
+    fn test_transcript() {
+        let session = "$ ls\r\na b\r\n$ vim\x1b[?1049h\x1b[HEDITOR\x1b[?1049l\r\n$ echo\r\n\x1b[31mred\x1b[0m\r\n";
+
+        let mut emulator = Emulator::new(10, 3).with_scrollback(100);
+        emulator.process_string(session);
+        let frame = emulator.transcript();
+        let rows: Vec<String> = (0..frame.height).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["$ ls", "a b", "$ vim", "$ echo", "red"]);
+        assert_eq!(frame.get_cell(4, 0).unwrap().fg, Color::Indexed(1));
+        assert!(!frame.cursor.visible);
+
+        let mut emulator = Emulator::new(10, 3)
+            .with_scrollback(100)
+            .with_alternate_screen_capture(true);
+        emulator.process_string(session);
+        let frame = emulator.transcript();
+        let rows: Vec<String> = (0..frame.height).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(
+            rows,
+            vec!["$ ls", "a b", "$ vim", "EDITOR", "$ echo", "red"]
+        );
+
+        // An episode still in progress is shown too
+        emulator.process_string("\x1b[?1049h\x1b[HPAGER");
+        let frame = emulator.transcript();
+        assert_eq!(row_text(&frame, frame.height - 1), "PAGER");
+
+        // ...after the line that started it, even with an earlier episode above
+        let mut emulator = Emulator::new(10, 5)
+            .with_scrollback(100)
+            .with_alternate_screen_capture(true);
+        emulator.process_string(
+            "$ vim\x1b[?1049h\x1b[HEDITOR\x1b[?1049l\r\n$ less\x1b[?1049h\x1b[HPAGER",
+        );
+        let frame = emulator.transcript();
+        let rows: Vec<String> = (0..frame.height).map(|r| row_text(&frame, r)).collect();
+        assert_eq!(rows, vec!["$ vim", "EDITOR", "$ less", "PAGER"]);
+    }
+
+    #[test]
+    fn test_line_editing_keeps_wide_characters_whole() {
+        let mut emulator = Emulator::new(6, 3);
+        emulator.process_string("a中b\x1b[1;3H\x1b[P");
+        emulator.process_string("\x1b[2;1H中文\x1b[2;2H\x1b[@");
+        emulator.process_string("\x1b[3;1H中文\x1b[3;4H\x1b[X");
+
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "a b");
+        assert_eq!(row_text(&frame, 1), "   文");
+        assert_eq!(row_text(&frame, 2), "中");
+        for row in 0..3 {
+            for col in 0..6 {
+                let cell = frame.get_cell(row, col).unwrap();
+                let lead = col.checked_sub(1).and_then(|c| frame.get_cell(row, c));
+                // Every placeholder follows the wide character it belongs to
+                assert_eq!(cell.width == 0, lead.is_some_and(|c| c.width == 2));
+            }
+        }
+
+        // A wide character pushed past the right edge doesn't leave its first half behind
+        let mut emulator = Emulator::new(4, 1);
+        emulator.process_string("ab中\x1b[1;1H\x1b[@");
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), " ab");
+        assert_eq!(frame.get_cell(0, 3).unwrap().width, 1);
+
+        // Erasing from either half of a wide character erases all of it
+        for (erase, expected) in [("K", "a"), ("1K", "   b"), ("J", "a"), ("1J", "   b")] {
+            for col in [2, 3] {
+                let mut emulator = Emulator::new(6, 1);
+                emulator.process_string(&format!("a中b\x1b[1;{col}H\x1b[{erase}"));
+                let frame = emulator.get_frame();
+                assert_eq!(row_text(&frame, 0), expected, "CSI {erase} at column {col}");
+                assert!((0..6).all(|c| frame.get_cell(0, c).unwrap().width == 1));
+            }
+        }
+
+        // Narrowing the terminal through a wide character drops it
+        let mut emulator = Emulator::new(4, 1);
+        emulator.process_string("ab中");
+        emulator.resize(3, 1);
+        let frame = emulator.get_frame();
+        assert_eq!(row_text(&frame, 0), "ab");
+        assert_eq!(frame.get_cell(0, 2).unwrap().width, 1);
+    }
+
+    #[test]
+    fn test_erase_uses_background_color() {
+        let mut emulator = Emulator::new(6, 3);
+        emulator.process_string("abc\x1b[44m\x1b[K\x1b[3;1H\x1b[42m\x1b[2K\x1b[0m");
+
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(0, 2).unwrap().bg, Color::Default);
+        assert_eq!(frame.get_cell(0, 5).unwrap().bg, Color::Indexed(4));
+        assert_eq!(frame.get_cell(1, 0).unwrap().bg, Color::Default);
+        assert_eq!(frame.get_cell(2, 0).unwrap().bg, Color::Indexed(2));
+
+        emulator.process_string("\x1b[41m\x1b[2J");
+        let frame = emulator.get_frame();
+        assert_eq!(frame.get_cell(1, 3).unwrap().bg, Color::Indexed(1));
+    }
+}
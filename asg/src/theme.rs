@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use anyhow::{Result, anyhow};
 use rgb::RGB8;
-use serde::{Deserialize, Serialize, de::Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Rgb {
@@ -27,7 +27,7 @@ impl Serialize for Rgb {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_u32(self.to_u32())
+        serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b))
     }
 }
 
@@ -42,7 +42,14 @@ impl<'de> Deserialize<'de> for Rgb {
             type Value = Rgb;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("an integer represeting an RGB color")
+                formatter.write_str("an integer or a \"#rrggbb\" string representing an RGB color")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                parse_hex_color(v).map(Rgb::from).map_err(E::custom)
             }
 
             fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
@@ -63,7 +70,7 @@ impl<'de> Deserialize<'de> for Rgb {
             }
         }
 
-        deserializer.deserialize_u32(V)
+        deserializer.deserialize_any(V)
     }
 }
 
@@ -87,13 +94,74 @@ impl From<Rgb> for RGB8 {
     }
 }
 
+/// A terminal color theme.
+///
+/// (De)serializes in the asciicast header format: `fg`/`bg` as `"#rrggbb"`
+/// strings and `palette` as 8 or 16 colon-separated colors. A plain array of
+/// colors is accepted for the palette as well.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Theme {
     pub bg: Rgb,
     pub fg: Rgb,
+    #[serde(
+        serialize_with = "serialize_palette",
+        deserialize_with = "deserialize_palette"
+    )]
     pub palette: [Rgb; 16],
 }
 
+fn serialize_palette<S>(palette: &[Rgb; 16], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let joined = palette
+        .iter()
+        .map(|c| format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b))
+        .collect::<Vec<_>>()
+        .join(":");
+    serializer.serialize_str(&joined)
+}
+
+fn deserialize_palette<'de, D>(deserializer: D) -> Result<[Rgb; 16], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawPalette {
+        Joined(String),
+        List(Vec<Rgb>),
+    }
+
+    let colors = match RawPalette::deserialize(deserializer)? {
+        RawPalette::Joined(s) => s
+            .split(':')
+            .map(|c| parse_hex_color(c).map(Rgb::from))
+            .collect::<Result<Vec<_>>>()
+            .map_err(D::Error::custom)?,
+        RawPalette::List(colors) => colors,
+    };
+
+    expand_palette(&colors).ok_or_else(|| {
+        D::Error::custom(format!(
+            "palette must have 8 or 16 colors, got {}",
+            colors.len()
+        ))
+    })
+}
+
+/// Expands an 8- or 16-color palette to 16 entries. With 8 colors, the bright
+/// variants reuse the normal ones, matching asciinema-player.
+fn expand_palette(colors: &[Rgb]) -> Option<[Rgb; 16]> {
+    match colors.len() {
+        8 => Some(std::array::from_fn(|i| colors[i % 8])),
+        16 => Some(std::array::from_fn(|i| colors[i])),
+        _ => None,
+    }
+}
+
 impl FromStr for Theme {
     type Err = anyhow::Error;
 
@@ -124,11 +192,22 @@ impl FromStr for Theme {
 }
 
 impl Theme {
+    /// Resolves an entry of the xterm 256-color palette. The first 16 entries
+    /// come from the theme; the rest are the standard color cube and grayscale ramp.
     pub fn get_color(&self, index: u8) -> RGB8 {
-        if index < 16 {
-            self.palette[index as usize].into()
-        } else {
-            self.fg.into()
+        match index {
+            0..=15 => self.palette[index as usize].into(),
+            16..=231 => {
+                // 6x6x6 color cube
+                const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+                let c = (index - 16) as usize;
+                RGB8::new(LEVELS[c / 36], LEVELS[(c % 36) / 6], LEVELS[c % 6])
+            }
+            232..=255 => {
+                // Grayscale ramp
+                let v = 8 + 10 * (index - 232);
+                RGB8::new(v, v, v)
+            }
         }
     }
 }
@@ -137,7 +216,7 @@ fn parse_hex_color(s: &str) -> Result<RGB8> {
     let s = s.trim();
     let s = s.trim_start_matches('#');
 
-    if s.len() != 6 {
+    if s.len() != 6 || !s.is_ascii() {
         return Err(anyhow!("Invalid hex color: {}", s));
     }
 